@@ -1,17 +1,55 @@
 use crate::{
-    Result, ask, checker, commit_review, commit_summary, config, convert_to_rust, criteria_verify,
-    design_advice, error::Error, explain, file, performance, task_generate, task_review,
+    Result, cache, chat,
+    client::Client,
+    config::{self, Config},
+    diff, export,
+    file::{self, Source},
+    git,
+    mode::Mode,
+    output::{self, Report},
+    prompt, test_generate,
 };
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use std::{
     io::{Read, stdin},
+    process::ExitCode,
     str::FromStr,
     sync::Arc,
 };
+use tokio::{sync::Semaphore, task::JoinSet};
+
+pub const EXIT_FINDINGS: u8 = 2;
 
 #[derive(Debug, Parser)]
 #[command(about, author, long_about = None, version)]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Backend (ollama, openai)
+    #[arg(long, short, env = "CCW_BACKEND")]
+    pub backend: Option<String>,
+
+    /// Maximum size of the result cache in MiB
+    #[arg(long, env = "CCW_CACHE_MAX_SIZE")]
+    pub cache_max_size: Option<u64>,
+
+    /// Split files larger than tokens into chunks (0 disables chunking)
+    #[arg(long, env = "CCW_CHUNK_SIZE")]
+    pub chunk_size: Option<u32>,
+
+    /// Commit to review (reads the diff with git)
+    #[arg(long, conflicts_with_all = ["range", "staged"])]
+    pub commit: Option<String>,
+
+    /// Check only the regions changed in a diff (from --staged, --commit, --range or stdin)
+    #[arg(long)]
+    pub diff: bool,
+
+    /// Lines of context around changed regions with --diff
+    #[arg(long)]
+    pub diff_context: Option<u32>,
+
     /// Dir
     #[arg(long, short)]
     pub dir: Option<String>,
@@ -20,16 +58,40 @@ pub struct Args {
     #[arg(long, short)]
     pub end_line: Option<u32>,
 
+    /// Exclude files matching glob (repeatable)
+    #[arg(long)]
+    pub exclude: Vec<String>,
+
+    /// Fail on findings at or above severity (low, medium, high, critical)
+    #[arg(long)]
+    pub fail_on: Option<String>,
+
     /// File
     #[arg(long, short)]
     pub file: Option<String>,
 
+    /// Output format (text, json, sarif, html)
+    #[arg(long, env = "CCW_FORMAT")]
+    pub format: Option<String>,
+
+    /// Include only files matching glob (repeatable)
+    #[arg(long)]
+    pub include: Vec<String>,
+
+    /// Number of files analysed in parallel
+    #[arg(long, short, env = "CCW_JOBS")]
+    pub jobs: Option<usize>,
+
     /// Keep alive in seconds
-    #[arg(long, short)]
+    #[arg(long, short, env = "CCW_KEEP_ALIVE")]
     pub keep_alive: Option<u16>,
 
+    /// Prefix code lines with their line numbers and report finding locations
+    #[arg(long, num_args = 0..=1, default_missing_value = "true")]
+    pub line_numbers: Option<bool>,
+
     /// Max attempts
-    #[arg(long)]
+    #[arg(long, env = "CCW_MAX_ATTEMPTS")]
     pub max_attempts: Option<u8>,
 
     /// Mode
@@ -37,91 +99,170 @@ pub struct Args {
     pub mode: Option<String>,
 
     /// Ollama model
-    #[arg(long, short)]
+    #[arg(long, short, env = "CCW_MODEL")]
     pub model: Option<String>,
 
+    /// Do not read or write the result cache
+    #[arg(long)]
+    pub no_cache: bool,
+
+    /// Write the whole report to a file instead of stdout
+    #[arg(long, conflicts_with = "output_dir")]
+    pub output: Option<String>,
+
+    /// Write one report per source file and an index to a directory
+    #[arg(long)]
+    pub output_dir: Option<String>,
+
+    /// Prompt template file (user message)
+    #[arg(long)]
+    pub prompt_template: Option<String>,
+
     /// Question
     #[arg(long, short)]
     pub question: Option<String>,
 
+    /// Range of commits to diff, e.g. main..HEAD (reads the diff with git)
+    #[arg(long, conflicts_with = "staged")]
+    pub range: Option<String>,
+
     /// Skip larger than tokens
-    #[arg(long)]
+    #[arg(long, env = "CCW_SKIP_LARGER")]
     pub skip_larger: Option<u32>,
 
+    /// Stall timeout in seconds
+    #[arg(long)]
+    pub stall_timeout: Option<u64>,
+
+    /// Review staged changes (reads the diff with git)
+    #[arg(long)]
+    pub staged: bool,
+
     /// Start line
     #[arg(long, short)]
     pub start_line: Option<u32>,
 
+    /// Stream response
+    #[arg(long)]
+    pub stream: Option<bool>,
+
+    /// System prompt file (replaces the built-in one)
+    #[arg(long)]
+    pub system_prompt: Option<String>,
+
+    /// System prompt file (appended to the built-in one)
+    #[arg(long)]
+    pub system_prompt_append: Option<String>,
+
     /// Timeout
-    #[arg(long, short)]
+    #[arg(long, short, env = "CCW_TIMEOUT")]
     pub timeout: Option<u64>,
+
+    /// Write generated tests into a sibling test file (test_generate mode)
+    #[arg(long)]
+    pub write_tests: bool,
 }
 
-#[derive(Clone, Debug)]
-pub enum Mode {
-    Ask,
-    Checker,
-    CommitReview,
-    CommitSummary,
-    ConvertToRust,
-    CriteriaVerify,
-    DesignAdvice,
-    Explain,
-    Performance,
-    TaskGenerate,
-    TaskReview,
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Result cache
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommand,
+    },
+    /// Interactive chat (ask or explain mode)
+    Chat {
+        /// Files added to the conversation
+        files: Vec<String>,
+
+        /// Load a saved transcript
+        #[arg(long)]
+        load: Option<String>,
+    },
+    /// Prompt templates
+    Prompts {
+        #[command(subcommand)]
+        command: PromptsCommand,
+    },
 }
 
-impl FromStr for Mode {
-    type Err = Error;
-
-    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-        let lowercase = s.to_string().to_lowercase();
-        let s = lowercase.as_str();
-        match s {
-            "ask" => Ok(Mode::Ask),
-            "checker" => Ok(Mode::Checker),
-            "commit_review" => Ok(Mode::CommitReview),
-            "commit_summary" => Ok(Mode::CommitSummary),
-            "convert_to_rust" => Ok(Mode::ConvertToRust),
-            "criteria_verify" => Ok(Mode::CriteriaVerify),
-            "design_advice" => Ok(Mode::DesignAdvice),
-            "explain" => Ok(Mode::Explain),
-            "performance" => Ok(Mode::Performance),
-            "task_generate" => Ok(Mode::TaskGenerate),
-            "task_review" => Ok(Mode::TaskReview),
-            _ => Ok(Mode::Checker),
-        }
-    }
+#[derive(Debug, Subcommand)]
+pub enum CacheCommand {
+    /// Remove all cached results
+    Clear,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum PromptsCommand {
+    /// Show the built-in prompts of a mode
+    Show { mode: String },
 }
 
-pub async fn run() -> Result<()> {
-    let args = Args::parse();
-    let config = Arc::new(config::load(args)?);
+pub async fn run() -> Result<ExitCode> {
+    let mut args = Args::parse();
+
+    if let Some(Command::Cache {
+        command: CacheCommand::Clear,
+    }) = &args.command
+    {
+        let removed = cache::clear()?;
+        let dir = cache::dir().unwrap_or_default();
+
+        println!("Removed {removed} cached results from {}", dir.display());
+
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if let Some(Command::Prompts {
+        command: PromptsCommand::Show { mode },
+    }) = &args.command
+    {
+        prompt::show(&Mode::from_str(mode)?);
+
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if let Some(Command::Chat { files, load }) = args.command.take() {
+        chat::run(config::load(args)?, &files, load.as_deref()).await?;
+
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let client = Client::new(config::load(args)?).print(true);
+    let config = client.config().clone();
+    let mut report = Report::new(&config);
 
     match config.mode {
         Mode::Checker
         | Mode::ConvertToRust
         | Mode::DesignAdvice
+        | Mode::DocGenerate
         | Mode::Explain
-        | Mode::Performance => {
-            let files = file::read_files(&config)?;
-            let files_count = files.len();
-            let mut i = 1;
-
-            for (file_name, code) in files {
-                println!("File {i} of {files_count} {file_name}");
-
-                match config.mode {
-                    Mode::Checker => checker::run(config.clone(), &code).await?,
-                    Mode::ConvertToRust => convert_to_rust::run(config.clone(), &code).await?,
-                    Mode::DesignAdvice => design_advice::run(config.clone(), &code).await?,
-                    Mode::Explain => explain::run(config.clone(), &code).await?,
-                    Mode::Performance => performance::run(config.clone(), &code).await?,
-                    _ => {}
-                }
+        | Mode::Performance
+        | Mode::TestGenerate => {
+            let files = if config.diff {
+                let diff = read_diff(&config).await?;
+
+                diff::sources(&config, &diff, git::toplevel().await.as_deref())?
+            } else {
+                file::read_files(&config)?
+            };
+
+            if config.jobs > 1 {
+                run_files_parallel(client, files, &mut report).await?;
+            } else {
+                let files_count = files.len();
 
-                i += 1;
+                for (i, source) in (1..).zip(files) {
+                    output::progress(
+                        &config,
+                        &format!("File {i} of {files_count} {}", source.name),
+                    );
+
+                    let file_report = client.run_file(&source).await?;
+
+                    report.files.push(file_report);
+                }
             }
         }
         Mode::CommitReview
@@ -129,24 +270,98 @@ pub async fn run() -> Result<()> {
         | Mode::CriteriaVerify
         | Mode::TaskGenerate
         | Mode::TaskReview => {
-            let mut code = String::new();
-            stdin().read_to_string(&mut code)?;
-
-            match config.mode {
-                Mode::CommitReview => commit_review::run(config.clone(), &code).await?,
-                Mode::CommitSummary => commit_summary::run(config.clone(), &code).await?,
-                Mode::CriteriaVerify => criteria_verify::run(config.clone(), &code).await?,
-                Mode::TaskGenerate => task_generate::run(config.clone(), &code).await?,
-                Mode::TaskReview => task_review::run(config.clone(), &code).await?,
-                _ => {}
+            let code = read_diff(&config).await?;
+
+            let file_report = client.run_diff(&code).await?;
+
+            report.files.push(file_report);
+        }
+        Mode::Ask => {
+            let file_report = client.run_question().await?;
+
+            report.files.push(file_report);
+        }
+    }
+
+    if config.output.is_none()
+        && config.output_dir.is_none()
+        && let Some(rendered) = output::render(&config, &report)?
+    {
+        println!("{rendered}");
+    }
+
+    export::write(&config, &report)?;
+
+    if config.write_tests && matches!(config.mode, Mode::TestGenerate) {
+        for file_report in &report.files {
+            if let Some(path) = test_generate::write(&config, file_report)? {
+                let hint = if path.ends_with("tests.rs") {
+                    " (declare it with `#[cfg(test)] mod tests;`)"
+                } else {
+                    ""
+                };
+
+                output::progress(
+                    &config,
+                    &format!("Tests written to {}{hint}", path.display()),
+                );
             }
         }
-        Mode::Ask => match config.mode {
-            Mode::Ask => ask::run(config.clone()).await?,
-            _ => {}
-        },
     }
 
+    if let Some(fail_on) = config.fail_on
+        && report.has_findings(fail_on)
+    {
+        return Ok(ExitCode::from(EXIT_FINDINGS));
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+async fn read_diff(config: &Config) -> Result<String> {
+    if let Some(diff) = git::read_diff(config).await? {
+        return Ok(diff);
+    }
+
+    let mut code = String::new();
+    stdin().read_to_string(&mut code)?;
+
+    Ok(code)
+}
+
+async fn run_files_parallel(client: Client, files: Vec<Source>, report: &mut Report) -> Result<()> {
+    let config = client.config().clone();
+    let files_count = files.len();
+    let semaphore = Arc::new(Semaphore::new(config.jobs));
+    let mut tasks = JoinSet::new();
+    let mut results = vec![None; files_count];
+
+    for (i, source) in files.into_iter().enumerate() {
+        let client = client.clone();
+        let semaphore = semaphore.clone();
+
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await?;
+            let (result, buffer) = output::capture(client.run_file(&source)).await;
+
+            Ok::<_, Box<dyn std::error::Error + Send + Sync>>((i, source.name, result?, buffer))
+        });
+    }
+
+    while let Some(task) = tasks.join_next().await {
+        let (i, file_name, result, buffer) = task??;
+
+        output::flush(
+            &config,
+            &format!("File {} of {files_count} {file_name}\n", i + 1),
+        );
+        output::flush_buffer(&config, &buffer);
+
+        results[i] = Some(result);
+    }
+
+    report.files.extend(results.into_iter().flatten());
+
     Ok(())
 }
 
@@ -1,7 +1,10 @@
 use crate::{
     Result,
+    backend::{self, Message},
     config::Config,
-    ollama::{self, Message},
+    error::Error,
+    mode::ModeResult,
+    prompt::{self, Vars},
 };
 use chrono::Utc;
 use std::sync::Arc;
@@ -25,31 +28,30 @@ Your responsibilities:
 
 Your goal: Provide expert-level, trustworthy, implementable answers to technical questions.";
 
-pub async fn run(config: Arc<Config>) -> Result<()> {
+pub const USER_TEMPLATE: &str = "Here is the question: {question}";
+
+pub async fn run(config: Arc<Config>) -> Result<ModeResult> {
     let start_date = Utc::now();
 
     let mut messages = vec![];
 
     let message = Message {
-        content: SYSTEM_PROMPT.to_string(),
+        content: prompt::system(&config, SYSTEM_PROMPT)?,
         role: "system".to_string(),
     };
     messages.push(message);
 
-    if let Some(question) = &config.question {
-        let prompt = format!("Here is the question: {question}");
+    if config.question.is_some() {
+        let vars = Vars::new(&config, "");
+        let prompt = prompt::user(&config, USER_TEMPLATE, &vars)?;
         let message = Message {
             content: prompt.clone(),
             role: "user".to_string(),
         };
         messages.push(message);
     } else {
-        println!("You need to provide --question with the question you want to ask.");
-
-        return Ok(());
+        return Err(Box::new(Error::QuestionMissing));
     }
 
-    ollama::run_request(config, messages, start_date).await?;
-
-    Ok(())
+    backend::run_request(config, messages, start_date).await
 }
@@ -0,0 +1,377 @@
+use crate::{
+    Result, cache,
+    config::Config,
+    error::Error,
+    finding,
+    mode::ModeResult,
+    ollama, openai,
+    output::{self, Format},
+    token,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::{
+        Arc, LazyLock, Mutex as StdMutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tokio::{
+    sync::Mutex,
+    time::{Duration, sleep, timeout},
+};
+
+pub const DEFAULT_CODE_MODEL: &str = "qwen3-coder:30b";
+pub const DEFAULT_CODE_NUM_CTX: u32 = 16384;
+pub const MIN_RESPONSE_TOKENS: u32 = 512;
+pub const RESPONSE_TOKENS: u32 = 4096;
+pub const RETRY_BASE_DELAY_MS: u64 = 1000;
+pub const RETRY_MAX_DELAY_MS: u64 = 30000;
+
+static CLIENTS: LazyLock<StdMutex<HashMap<u64, reqwest::Client>>> =
+    LazyLock::new(|| StdMutex::new(HashMap::new()));
+static CONTEXT_LENGTHS: LazyLock<Mutex<HashMap<String, Option<u32>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Clone, Debug)]
+pub enum Backend {
+    Ollama,
+    OpenAi,
+}
+
+impl FromStr for Backend {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let lowercase = s.to_string().to_lowercase();
+        let s = lowercase.as_str();
+        match s {
+            "ollama" => Ok(Backend::Ollama),
+            "openai" => Ok(Backend::OpenAi),
+            _ => Err(Error::UnknownBackend(s.to_string())),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Completion {
+    pub completion_tokens: Option<u32>,
+    pub content: String,
+    pub prompt_tokens: Option<u32>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Message {
+    pub content: String,
+    pub role: String,
+}
+
+pub fn model(config: &Config) -> String {
+    config
+        .model
+        .clone()
+        .unwrap_or(DEFAULT_CODE_MODEL.to_string())
+}
+
+pub fn client(config: &Config) -> Result<reqwest::Client> {
+    let mut clients = CLIENTS.lock().unwrap_or_else(|e| e.into_inner());
+
+    if let Some(client) = clients.get(&config.timeout) {
+        return Ok(client.clone());
+    }
+
+    let client = reqwest::ClientBuilder::new()
+        .connect_timeout(Duration::from_secs(config.timeout))
+        .timeout(Duration::from_secs(config.timeout))
+        .build()?;
+
+    clients.insert(config.timeout, client.clone());
+
+    Ok(client)
+}
+
+pub async fn context_length(config: &Config) -> Option<u32> {
+    let key = format!("{}|{}", config.host, model(config));
+    let mut context_lengths = CONTEXT_LENGTHS.lock().await;
+
+    if let Some(context_length) = context_lengths.get(&key) {
+        return *context_length;
+    }
+
+    let context_length = match config.backend {
+        Backend::Ollama => ollama::context_length(config).await,
+        Backend::OpenAi => openai::context_length(config).await,
+    };
+
+    let context_length = match context_length {
+        Ok(context_length) => context_length,
+        Err(e) => {
+            output::warn(
+                config,
+                &format!("Unable to read the model context length: {e}"),
+            );
+
+            None
+        }
+    };
+
+    context_lengths.insert(key, context_length);
+
+    context_length
+}
+
+pub async fn check_status(response: reqwest::Response) -> Result<reqwest::Response> {
+    let status = response.status();
+
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    let body = response.text().await.unwrap_or_default();
+
+    Err(Box::new(Error::RequestFailed {
+        body,
+        status: status.as_u16(),
+    }))
+}
+
+pub fn is_retryable(e: &(dyn std::error::Error + Send + Sync + 'static)) -> bool {
+    if let Some(error) = e.downcast_ref::<Error>() {
+        return match error {
+            Error::RequestFailed { status, .. } => {
+                *status == 408 || *status == 429 || *status >= 500
+            }
+            Error::StreamStalled => true,
+            _ => false,
+        };
+    }
+
+    if let Some(error) = e.downcast_ref::<reqwest::Error>() {
+        return error.is_timeout() || error.is_connect() || error.is_request() || error.is_body();
+    }
+
+    false
+}
+
+pub fn retry_delay(attempt: u8) -> Duration {
+    let exponent = u32::from(attempt.saturating_sub(1)).min(16);
+    let delay = RETRY_BASE_DELAY_MS
+        .saturating_mul(2_u64.pow(exponent))
+        .min(RETRY_MAX_DELAY_MS);
+
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| u64::from(duration.subsec_nanos()))
+        .unwrap_or_default();
+    let jitter = nanos % (delay / 2 + 1);
+
+    Duration::from_millis(delay / 2 + jitter)
+}
+
+pub async fn request(
+    config: Arc<Config>,
+    messages: Vec<Message>,
+    num_ctx: Option<u32>,
+    format: Option<&Value>,
+    on_chunk: &(dyn Fn(&str) + Send + Sync),
+) -> Result<Completion> {
+    let max_attempts = config.max_attempts.max(1);
+    let mut attempt = 1;
+    let emitted = AtomicBool::new(false);
+    let on_chunk = |chunk: &str| {
+        emitted.store(true, Ordering::Relaxed);
+        on_chunk(chunk);
+    };
+
+    loop {
+        let mark = output::mark();
+        emitted.store(false, Ordering::Relaxed);
+
+        let response = match config.backend {
+            Backend::Ollama => {
+                ollama::request(config.clone(), messages.clone(), num_ctx, format, &on_chunk).await
+            }
+            Backend::OpenAi => {
+                openai::request(config.clone(), messages.clone(), format, &on_chunk).await
+            }
+        };
+
+        let e = match response {
+            Ok(response) => return Ok(response),
+            Err(e) if !is_retryable(e.as_ref()) => return Err(e),
+            Err(e) => e,
+        };
+
+        if attempt >= max_attempts {
+            return Err(Box::new(Error::AttemptsExhausted {
+                attempts: attempt,
+                last_error: e.to_string(),
+            }));
+        }
+
+        if emitted.load(Ordering::Relaxed) {
+            output::discard(&config, mark);
+        }
+
+        let delay = retry_delay(attempt);
+
+        output::warn(
+            &config,
+            &format!(
+                "\nError: {e}\nRetrying in {} ms (attempt {} of {max_attempts})...",
+                delay.as_millis(),
+                attempt + 1
+            ),
+        );
+
+        sleep(delay).await;
+
+        attempt += 1;
+    }
+}
+
+pub async fn read_lines(
+    config: &Config,
+    mut response: reqwest::Response,
+    mut on_line: impl FnMut(&str) -> Result<bool>,
+) -> Result<()> {
+    let mut buffer = vec![];
+
+    loop {
+        let Ok(chunk) = timeout(Duration::from_secs(config.stall_timeout), response.chunk()).await
+        else {
+            output::warn(
+                config,
+                &format!(
+                    "\nNo response chunk received in {} seconds.",
+                    config.stall_timeout
+                ),
+            );
+
+            return Err(Box::new(Error::StreamStalled));
+        };
+
+        let Some(chunk) = chunk? else {
+            break;
+        };
+
+        buffer.extend_from_slice(&chunk);
+
+        while let Some(position) = buffer.iter().position(|byte| *byte == b'\n') {
+            let line = buffer.drain(..=position).collect::<Vec<u8>>();
+            let line = std::str::from_utf8(&line)?.trim();
+
+            if !line.is_empty() && on_line(line)? {
+                return Ok(());
+            }
+        }
+    }
+
+    let line = std::str::from_utf8(&buffer)?.trim();
+
+    if !line.is_empty() {
+        on_line(line)?;
+    }
+
+    Ok(())
+}
+
+pub async fn run_request(
+    config: Arc<Config>,
+    mut messages: Vec<Message>,
+    start_date: DateTime<Utc>,
+) -> Result<ModeResult> {
+    let format = if config.format != Format::Text && finding::has_findings(&config.mode) {
+        let message = Message {
+            content: finding::JSON_PROMPT.to_string(),
+            role: "system".to_string(),
+        };
+        messages.push(message);
+
+        Some(finding::schema())
+    } else {
+        None
+    };
+
+    let tokens = messages
+        .iter()
+        .map(|message| token::estimate(&message.content))
+        .sum::<usize>();
+
+    let mut num_ctx = u32::try_from(tokens)? + RESPONSE_TOKENS;
+
+    let context_length = context_length(&config).await;
+
+    if let Some(context_length) = context_length {
+        num_ctx = num_ctx.min(context_length);
+    }
+
+    let mut result = ModeResult {
+        cached: false,
+        completion_tokens: None,
+        estimated_prompt_tokens: tokens,
+        finished_at: start_date,
+        model: model(&config),
+        num_ctx,
+        prompt_tokens: None,
+        skipped: false,
+        started_at: start_date,
+        text: None,
+    };
+
+    output::started(&config, &result);
+
+    if let Some(skip_larger) = config.skip_larger
+        && num_ctx > skip_larger
+    {
+        result.skipped = true;
+        result.finished_at = Utc::now();
+
+        return Ok(result);
+    }
+
+    if let Some(context_length) = context_length
+        && u32::try_from(tokens)? + MIN_RESPONSE_TOKENS > context_length
+    {
+        return Err(Box::new(Error::ContextTooLarge {
+            context_length,
+            tokens,
+        }));
+    }
+
+    let key = cache::key(&config, &messages, num_ctx, format.as_ref())?;
+
+    let completion = if let Some(completion) = cache::load(&config, &key) {
+        output::progress(&config, "Using cached result.\n");
+        output::chunk(&config, &completion.content);
+        result.cached = true;
+
+        completion
+    } else {
+        let print_chunk = |chunk: &str| output::chunk(&config, chunk);
+
+        let completion = request(
+            config.clone(),
+            messages,
+            Some(num_ctx),
+            format.as_ref(),
+            &print_chunk,
+        )
+        .await?;
+
+        cache::store(&config, &key, &completion);
+
+        completion
+    };
+
+    result.completion_tokens = completion.completion_tokens;
+    result.finished_at = Utc::now();
+    result.prompt_tokens = completion.prompt_tokens;
+    result.text = Some(completion.content);
+
+    Ok(result)
+}
@@ -0,0 +1,149 @@
+use crate::{
+    Result,
+    backend::{self, Backend, Completion, Message},
+    config::Config,
+    output,
+};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use std::{
+    env,
+    fs::{self, File},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+pub const CACHE_DIR: &str = "ccw";
+pub const CACHE_VERSION: u32 = 1;
+pub const DEFAULT_CACHE_MAX_SIZE: u64 = 100;
+
+pub fn clear() -> Result<usize> {
+    let Some(dir) = dir() else {
+        return Ok(0);
+    };
+
+    let entries = entries(&dir)?;
+
+    for (path, _, _) in &entries {
+        fs::remove_file(path)?;
+    }
+
+    Ok(entries.len())
+}
+
+pub fn dir() -> Option<PathBuf> {
+    if let Ok(cache_home) = env::var("XDG_CACHE_HOME")
+        && !cache_home.is_empty()
+    {
+        return Some(Path::new(&cache_home).join(CACHE_DIR));
+    }
+
+    let home = env::var("HOME").ok()?;
+
+    Some(Path::new(&home).join(".cache").join(CACHE_DIR))
+}
+
+pub fn key(
+    config: &Config,
+    messages: &[Message],
+    num_ctx: u32,
+    format: Option<&Value>,
+) -> Result<String> {
+    let backend = match config.backend {
+        Backend::Ollama => "ollama",
+        Backend::OpenAi => "openai",
+    };
+
+    let input = json!({
+        "backend": backend,
+        "format": format,
+        "messages": messages,
+        "mode": config.mode.to_string(),
+        "model": backend::model(config),
+        "num_ctx": num_ctx,
+        "version": CACHE_VERSION
+    });
+
+    let hash = Sha256::digest(serde_json::to_vec(&input)?);
+
+    Ok(hash.iter().map(|byte| format!("{byte:02x}")).collect())
+}
+
+pub fn load(config: &Config, key: &str) -> Option<Completion> {
+    if !config.cache {
+        return None;
+    }
+
+    let path = dir()?.join(format!("{key}.json"));
+    let completion = serde_json::from_str(&fs::read_to_string(&path).ok()?).ok()?;
+
+    if let Ok(file) = File::options().write(true).open(&path) {
+        let _ = file.set_modified(SystemTime::now());
+    }
+
+    Some(completion)
+}
+
+pub fn store(config: &Config, key: &str, completion: &Completion) {
+    if !config.cache {
+        return;
+    }
+
+    let Some(dir) = dir() else {
+        return;
+    };
+
+    if let Err(e) = write(&dir, key, completion, config.cache_max_size) {
+        output::warn(config, &format!("Unable to write the result cache: {e}"));
+    }
+}
+
+fn entries(dir: &Path) -> Result<Vec<(PathBuf, u64, SystemTime)>> {
+    let mut result = vec![];
+
+    if !dir.is_dir() {
+        return Ok(result);
+    }
+
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+
+        if path.extension().is_none_or(|extension| extension != "json") {
+            continue;
+        }
+
+        let metadata = entry.metadata()?;
+        result.push((path, metadata.len(), metadata.modified()?));
+    }
+
+    Ok(result)
+}
+
+fn prune(dir: &Path, max_size: u64) -> Result<()> {
+    let mut entries = entries(dir)?;
+    let mut size = entries.iter().map(|(_, len, _)| len).sum::<u64>();
+
+    entries.sort_by_key(|(_, _, modified)| *modified);
+
+    for (path, len, _) in entries {
+        if size <= max_size {
+            break;
+        }
+
+        fs::remove_file(path)?;
+        size -= len;
+    }
+
+    Ok(())
+}
+
+fn write(dir: &Path, key: &str, completion: &Completion, max_size: u64) -> Result<()> {
+    fs::create_dir_all(dir)?;
+    fs::write(
+        dir.join(format!("{key}.json")),
+        serde_json::to_string(completion)?,
+    )?;
+
+    prune(dir, max_size.saturating_mul(1024 * 1024))
+}
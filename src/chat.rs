@@ -0,0 +1,265 @@
+use crate::{
+    Result, ask,
+    backend::{self, Message},
+    config::Config,
+    explain,
+    language::Registry,
+    mode::Mode,
+    output::{self, Format},
+    prompt, token,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path, sync::Arc};
+use tokio::io::{AsyncBufReadExt, BufReader, stdin};
+
+pub const HELP: &str = "Commands:
+  /add <file>...   add files to the conversation
+  /clear           start a new conversation
+  /help            show this help
+  /load <file>     load a saved transcript
+  /model [name]    show or switch the model
+  /save <file>     save the transcript
+  /exit            leave the chat (or press Ctrl-D)";
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Transcript {
+    pub messages: Vec<Message>,
+    pub mode: String,
+    pub model: String,
+}
+
+#[derive(Debug)]
+pub struct Chat {
+    config: Arc<Config>,
+    messages: Vec<Message>,
+    registry: Registry,
+}
+
+impl Chat {
+    pub fn new(config: Config) -> Result<Self> {
+        let mode = match config.mode {
+            Mode::Explain => Mode::Explain,
+            _ => Mode::Ask,
+        };
+        let config = Config {
+            format: Format::Text,
+            mode,
+            output: None,
+            output_dir: None,
+            ..config
+        };
+        let registry = Registry::new(&config.languages);
+
+        let mut chat = Self {
+            config: Arc::new(config),
+            messages: vec![],
+            registry,
+        };
+        chat.clear()?;
+
+        Ok(chat)
+    }
+
+    pub fn add(&mut self, file: &str) -> Result<usize> {
+        let code = fs::read_to_string(file)?;
+        let language = self
+            .registry
+            .detect(Path::new(file))
+            .unwrap_or("text")
+            .to_string();
+
+        let content = format!("Here is the {language} code from {file}:\n{code}");
+        let tokens = token::estimate(&content);
+
+        self.messages.push(Message {
+            content,
+            role: "user".to_string(),
+        });
+
+        Ok(tokens)
+    }
+
+    pub async fn ask(&mut self, question: &str) -> Result<Option<String>> {
+        self.messages.push(Message {
+            content: question.to_string(),
+            role: "user".to_string(),
+        });
+
+        let result =
+            backend::run_request(self.config.clone(), self.messages.clone(), Utc::now()).await;
+
+        let result = match result {
+            Ok(result) => result,
+            Err(e) => {
+                self.messages.pop();
+
+                return Err(e);
+            }
+        };
+
+        output::finished(&self.config, &result);
+
+        let Some(text) = result.text else {
+            self.messages.pop();
+
+            return Ok(None);
+        };
+
+        self.messages.push(Message {
+            content: text.clone(),
+            role: "assistant".to_string(),
+        });
+
+        Ok(Some(text))
+    }
+
+    pub fn clear(&mut self) -> Result<()> {
+        let system_prompt = match self.config.mode {
+            Mode::Explain => explain::SYSTEM_PROMPT,
+            _ => ask::SYSTEM_PROMPT,
+        };
+
+        self.messages = vec![Message {
+            content: prompt::system(&self.config, system_prompt)?,
+            role: "system".to_string(),
+        }];
+
+        Ok(())
+    }
+
+    pub fn load(&mut self, path: &str) -> Result<()> {
+        let transcript = serde_json::from_str::<Transcript>(&fs::read_to_string(path)?)?;
+
+        self.messages = transcript.messages;
+
+        Ok(())
+    }
+
+    pub fn messages(&self) -> &[Message] {
+        &self.messages
+    }
+
+    pub fn model(&self) -> String {
+        backend::model(&self.config)
+    }
+
+    pub fn save(&self, path: &str) -> Result<()> {
+        let transcript = Transcript {
+            messages: self.messages.clone(),
+            mode: self.config.mode.to_string(),
+            model: self.model(),
+        };
+
+        fs::write(path, serde_json::to_string_pretty(&transcript)?)?;
+
+        Ok(())
+    }
+
+    pub fn set_model(&mut self, model: &str) {
+        self.config = Arc::new(Config {
+            model: Some(model.to_string()),
+            ..(*self.config).clone()
+        });
+    }
+
+    async fn command(&mut self, line: &str) -> Result<bool> {
+        let mut words = line.split_whitespace();
+        let command = words.next().unwrap_or_default();
+        let args = words.collect::<Vec<&str>>();
+
+        match (command, args.as_slice()) {
+            ("/add", files) if !files.is_empty() => {
+                for file in files {
+                    let tokens = self.add(file)?;
+
+                    self.print(&format!("Added {file} ({tokens} tokens)."));
+                }
+            }
+            ("/clear", []) => {
+                self.clear()?;
+
+                self.print("Started a new conversation.");
+            }
+            ("/exit" | "/quit", []) => return Ok(false),
+            ("/help", []) => self.print(HELP),
+            ("/load", [path]) => {
+                self.load(path)?;
+
+                self.print(&format!(
+                    "Loaded {} messages from {path}.",
+                    self.messages.len()
+                ));
+            }
+            ("/model", []) => self.print(&format!("Model: {}", self.model())),
+            ("/model", [model]) => {
+                self.set_model(model);
+
+                self.print(&format!("Model set to {model}."));
+            }
+            ("/save", [path]) => {
+                self.save(path)?;
+
+                self.print(&format!(
+                    "Saved {} messages to {path}.",
+                    self.messages.len()
+                ));
+            }
+            _ => self.print(&format!(
+                "Unknown command {line}. Type /help for the list of commands."
+            )),
+        }
+
+        Ok(true)
+    }
+
+    fn print(&self, text: &str) {
+        output::print(&self.config, &format!("{text}\n"));
+    }
+}
+
+pub async fn run(config: Config, files: &[String], load: Option<&str>) -> Result<()> {
+    let mut chat = Chat::new(config)?;
+
+    if let Some(path) = load {
+        chat.command(&format!("/load {path}")).await?;
+    }
+
+    for file in files {
+        chat.command(&format!("/add {file}")).await?;
+    }
+
+    chat.print(&format!(
+        "Chatting with {}. Type /help for the list of commands.",
+        chat.model()
+    ));
+
+    let mut lines = BufReader::new(stdin()).lines();
+
+    loop {
+        output::print(&chat.config, "\n> ");
+
+        let Some(line) = lines.next_line().await? else {
+            break;
+        };
+        let line = line.trim();
+
+        if line.is_empty() {
+            continue;
+        }
+
+        let result = if line.starts_with('/') {
+            chat.command(line).await
+        } else {
+            chat.ask(line).await.map(|_| true)
+        };
+
+        match result {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(e) => chat.print(&format!("Error: {e}")),
+        }
+    }
+
+    Ok(())
+}
@@ -1,7 +1,10 @@
 use crate::{
     Result,
+    backend::{self, Message},
     config::Config,
-    ollama::{self, Message},
+    file::Source,
+    mode::ModeResult,
+    prompt::{self, Vars},
 };
 use chrono::Utc;
 use std::sync::Arc;
@@ -41,10 +44,13 @@ Output Requirements:
 Problem summary
 (A short title capturing the issue)
 
+Severity: critical | high | medium | low
+(Exactly one of these four words)
+
 Problem detailed description
 - Why this is a real problem
 - Under what conditions it occurs
-- Potential impact/severity
+- Potential impact
 - What part of the provided code demonstrates the issue
 
 Relevant code snippet (optional)
@@ -57,25 +63,39 @@ Formatting rules:
 - No fluff. No praise. No generic advice.
 - Only output issues that you can clearly justify using the provided code.";
 
-pub async fn run(config: Arc<Config>, code: &str) -> Result<()> {
+pub const USER_TEMPLATE: &str = "Here is the {language} code: {code}";
+
+pub async fn run(config: Arc<Config>, source: &Source) -> Result<ModeResult> {
     let start_date = Utc::now();
 
+    let vars = Vars {
+        file_name: &source.name,
+        language: &source.language,
+        ..Vars::new(&config, &source.code)
+    };
+
     let mut messages = vec![];
 
     let message = Message {
-        content: SYSTEM_PROMPT.to_string(),
+        content: prompt::system(&config, SYSTEM_PROMPT)?,
         role: "system".to_string(),
     };
     messages.push(message);
 
-    let prompt = format!("Here is the code: {code}");
+    if let Some(context) = &source.context {
+        let message = Message {
+            content: context.clone(),
+            role: "user".to_string(),
+        };
+        messages.push(message);
+    }
+
+    let prompt = prompt::user(&config, USER_TEMPLATE, &vars)?;
     let message = Message {
         content: prompt.clone(),
         role: "user".to_string(),
     };
     messages.push(message);
 
-    ollama::run_request(config, messages, start_date).await?;
-
-    Ok(())
+    backend::run_request(config, messages, start_date).await
 }
@@ -0,0 +1,535 @@
+use crate::{Result, config::Config, file::Source, prompt, token};
+
+pub const CHUNK_MIN_TOKENS: usize = 256;
+pub const CHUNK_RESERVED_TOKENS: u32 = 5120;
+pub const CONTEXT_MAX_LINES: usize = 40;
+pub const CONTEXT_PREFIXES: &[&str] = &[
+    "#import", "#include", "from ", "import ", "mod ", "package ", "pub mod ", "pub use ",
+    "require", "use ", "using ",
+];
+
+pub const DEFINITION_KEYWORDS: &[&str] = &[
+    "class",
+    "def",
+    "defmodule",
+    "defp",
+    "enum",
+    "fn",
+    "func",
+    "function",
+    "impl",
+    "interface",
+    "macro_rules!",
+    "module",
+    "object",
+    "struct",
+    "sub",
+    "trait",
+];
+
+#[derive(Clone, Debug, Default)]
+struct Scanner {
+    block_comment: bool,
+    depth: usize,
+}
+
+impl Scanner {
+    fn scan(&mut self, line: &str, language: &str) {
+        let chars = line.chars().collect::<Vec<char>>();
+        let line_comment = line_comment(language);
+        let mut i = 0;
+
+        while i < chars.len() {
+            let c = chars[i];
+            let next = chars.get(i + 1).copied();
+
+            if self.block_comment {
+                if c == '*' && next == Some('/') {
+                    self.block_comment = false;
+                    i += 1;
+                }
+                i += 1;
+
+                continue;
+            }
+
+            if c == '/' && next == Some('*') && line_comment != "#" {
+                self.block_comment = true;
+                i += 2;
+
+                continue;
+            }
+
+            if chars[i..].starts_with(&line_comment.chars().collect::<Vec<char>>()) {
+                return;
+            }
+
+            match c {
+                '"' | '`' => i = skip_string(&chars, i, c),
+                '\'' if language == "Rust" => {
+                    if next == Some('\\') {
+                        i = skip_string(&chars, i, c);
+                    } else if chars.get(i + 2) == Some(&'\'') {
+                        i += 2;
+                    }
+                }
+                '\'' => i = skip_string(&chars, i, c),
+                '{' | '(' | '[' => self.depth += 1,
+                '}' | ')' | ']' => self.depth = self.depth.saturating_sub(1),
+                _ => {}
+            }
+
+            i += 1;
+        }
+    }
+}
+
+pub fn max_tokens(config: &Config, context_length: Option<u32>) -> Result<usize> {
+    if config.chunk_size == 0 {
+        return Ok(0);
+    }
+
+    let chunk_size = match context_length {
+        Some(context_length) => config.chunk_size.min(context_length),
+        None => config.chunk_size,
+    };
+
+    let (system_prompt, _) = prompt::builtin(&config.mode);
+    let system_prompt = prompt::system(config, system_prompt)?;
+    let reserved = CHUNK_RESERVED_TOKENS.min(chunk_size / 3);
+    let tokens = usize::try_from(chunk_size - reserved)?;
+
+    Ok(tokens
+        .saturating_sub(token::estimate(&system_prompt))
+        .max(CHUNK_MIN_TOKENS))
+}
+
+pub fn regions(source: &Source, context_lines: u32, max_tokens: usize) -> Vec<Source> {
+    let lines = source.code.split_inclusive('\n').collect::<Vec<&str>>();
+
+    if lines.is_empty() {
+        return vec![];
+    }
+
+    let depths = depths(&lines, &source.language);
+    let items = items(&lines, &source.language);
+    let context_lines = usize::try_from(context_lines).unwrap_or_default();
+    let mut ranges: Vec<(usize, usize)> = vec![];
+
+    for (first, last) in &source.changes {
+        let first = line_index(source, *first, lines.len());
+        let last = line_index(source, *last, lines.len()).max(first);
+
+        for line in first..=last {
+            if lines[line].trim().is_empty()
+                || is_comment(lines[line])
+                || ranges
+                    .last()
+                    .is_some_and(|(start, end)| (*start..*end).contains(&line))
+            {
+                continue;
+            }
+
+            let (start, end) = enclosing_definition(&lines, &depths, &source.language, line)
+                .or_else(|| enclosing_item(&items, &depths, lines.len(), line))
+                .unwrap_or((line, line + 1));
+
+            ranges.push((start, end));
+        }
+
+        if ranges.is_empty()
+            || !ranges
+                .iter()
+                .any(|(start, end)| *start <= last && *end > first)
+        {
+            ranges.push((first, last + 1));
+        }
+    }
+
+    ranges.sort_unstable();
+
+    let mut merged: Vec<(usize, usize)> = vec![];
+
+    for (start, end) in ranges {
+        let start = start.saturating_sub(context_lines);
+        let end = (end + context_lines).min(lines.len());
+
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+
+    let context = context(&lines);
+    let mut result = vec![];
+
+    for (start, end) in merged {
+        let first_line = source.start_line + u32::try_from(start).unwrap_or_default();
+        let last_line = source.start_line + u32::try_from(end).unwrap_or_default() - 1;
+
+        let changes = source
+            .changes
+            .iter()
+            .filter(|(first, last)| *first <= last_line && *last >= first_line)
+            .map(|(first, last)| {
+                if first == last {
+                    first.to_string()
+                } else {
+                    format!("{first}-{last}")
+                }
+            })
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        let mut header = format!(
+            "This is a changed region of {} (lines {first_line}-{last_line}). The lines changed by the diff are: {changes}. Focus on problems introduced or affected by these changes; the other lines are shown only as context and the rest of the file is not shown.",
+            source.name
+        );
+
+        if start > 0 && !context.is_empty() {
+            header.push_str(&format!(
+                "\n\nImports and declarations from the top of the file:\n{context}"
+            ));
+        }
+
+        let region = Source {
+            changes: vec![],
+            code: lines[start..end].concat(),
+            context: Some(header.clone()),
+            language: source.language.clone(),
+            name: source.name.clone(),
+            start_line: first_line,
+        };
+
+        let parts = split(&region, max_tokens);
+
+        if parts.len() == 1 {
+            result.push(region);
+
+            continue;
+        }
+
+        for mut part in parts {
+            part.context = Some(format!("{header}\n\n{}", part.context.unwrap_or_default()));
+            result.push(part);
+        }
+    }
+
+    result
+}
+
+pub fn split(source: &Source, max_tokens: usize) -> Vec<Source> {
+    if max_tokens == 0 || token::estimate(&source.code) <= max_tokens {
+        return vec![source.clone()];
+    }
+
+    let lines = source.code.split_inclusive('\n').collect::<Vec<&str>>();
+    let items = items(&lines, &source.language);
+    let context = context(&lines);
+
+    let mut ranges = vec![];
+    let mut start = 0;
+    let mut size = 0;
+
+    for (i, item) in items.iter().enumerate() {
+        let end = items.get(i + 1).copied().unwrap_or(lines.len());
+        let item_size = lines[*item..end]
+            .iter()
+            .map(|line| token::estimate(line))
+            .sum::<usize>();
+
+        if size > 0 && size + item_size > max_tokens {
+            ranges.push((start, *item));
+            start = *item;
+            size = 0;
+        }
+
+        if item_size > max_tokens {
+            ranges.extend(split_lines(&lines, start, end, max_tokens));
+            start = end;
+            size = 0;
+
+            continue;
+        }
+
+        size += item_size;
+    }
+
+    if start < lines.len() {
+        ranges.push((start, lines.len()));
+    }
+
+    let parts = ranges.len();
+
+    ranges
+        .into_iter()
+        .enumerate()
+        .map(|(i, (start, end))| {
+            let first_line = source.start_line + u32::try_from(start).unwrap_or_default();
+            let last_line = source.start_line + u32::try_from(end).unwrap_or_default() - 1;
+
+            let mut header = format!(
+                "This is part {} of {parts} of {} (lines {first_line}-{last_line}). The other parts are analysed separately, so do not report problems caused only by code that is not shown here.",
+                i + 1,
+                source.name
+            );
+
+            if start > 0 && !context.is_empty() {
+                header.push_str(&format!(
+                    "\n\nImports and declarations from the top of the file:\n{context}"
+                ));
+            }
+
+            if let Some(enclosing) = enclosing(&lines, &items, start) {
+                header.push_str(&format!(
+                    "\n\nThis part continues the definition starting with:\n{enclosing}"
+                ));
+            }
+
+            Source {
+                changes: vec![],
+                code: lines[start..end].concat(),
+                context: Some(header),
+                language: source.language.clone(),
+                name: source.name.clone(),
+                start_line: first_line,
+            }
+        })
+        .collect()
+}
+
+fn context(lines: &[&str]) -> String {
+    let mut result = vec![];
+    let mut open = false;
+
+    for line in lines {
+        let trimmed = line.trim_start();
+
+        if open
+            || CONTEXT_PREFIXES
+                .iter()
+                .any(|prefix| trimmed.starts_with(prefix))
+        {
+            result.push(*line);
+            open =
+                (open || trimmed.contains(['{', '('])) && !trimmed.trim_end().ends_with([';', ')']);
+        }
+
+        if result.len() >= CONTEXT_MAX_LINES {
+            break;
+        }
+    }
+
+    result.concat()
+}
+
+fn block_end(lines: &[&str], depths: &[usize], language: &str, start: usize) -> usize {
+    if is_indentation_based(language) {
+        let indent = indentation(lines[start]);
+        let mut end = start + 1;
+
+        for (i, line) in lines.iter().enumerate().skip(start + 1) {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            if indentation(line) <= indent {
+                break;
+            }
+
+            end = i + 1;
+        }
+
+        return end;
+    }
+
+    let mut opened = false;
+
+    for i in start..lines.len() {
+        if depths[i + 1] > depths[start] {
+            opened = true;
+        } else if opened || lines[i].trim_end().ends_with(';') {
+            return i + 1;
+        }
+    }
+
+    start + 1
+}
+
+fn depths(lines: &[&str], language: &str) -> Vec<usize> {
+    let mut result = vec![0];
+    let mut scanner = Scanner::default();
+
+    for line in lines {
+        scanner.scan(line, language);
+        result.push(scanner.depth);
+    }
+
+    result
+}
+
+fn enclosing(lines: &[&str], items: &[usize], start: usize) -> Option<String> {
+    if items.contains(&start) {
+        return None;
+    }
+
+    let item = items.iter().rev().find(|item| **item < start)?;
+
+    lines[*item..start]
+        .iter()
+        .find(|line| !is_comment(line))
+        .map(|line| line.trim_end().to_string())
+}
+
+fn enclosing_definition(
+    lines: &[&str],
+    depths: &[usize],
+    language: &str,
+    line: usize,
+) -> Option<(usize, usize)> {
+    for start in (0..=line).rev() {
+        if !is_definition(lines[start]) {
+            continue;
+        }
+
+        let end = block_end(lines, depths, language, start);
+
+        if end > line {
+            return Some((start, end));
+        }
+    }
+
+    None
+}
+
+fn enclosing_item(
+    items: &[usize],
+    depths: &[usize],
+    lines_count: usize,
+    line: usize,
+) -> Option<(usize, usize)> {
+    if depths[line] == 0 {
+        return None;
+    }
+
+    let position = items.iter().rposition(|item| *item <= line)?;
+    let end = items.get(position + 1).copied().unwrap_or(lines_count);
+
+    Some((items[position], end))
+}
+
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+fn is_comment(line: &str) -> bool {
+    let trimmed = line.trim_start();
+
+    ["//", "/*", "*", "#", "--", "%", "@"]
+        .iter()
+        .any(|prefix| trimmed.starts_with(prefix))
+}
+
+fn is_definition(line: &str) -> bool {
+    if is_comment(line) {
+        return false;
+    }
+
+    line.split_whitespace()
+        .take(6)
+        .map(|word| word.split(['(', '<', ':', '{']).next().unwrap_or_default())
+        .any(|word| DEFINITION_KEYWORDS.contains(&word))
+}
+
+fn is_indentation_based(language: &str) -> bool {
+    matches!(
+        language,
+        "Elixir" | "Haskell" | "Lua" | "Nim" | "Python" | "Ruby"
+    )
+}
+
+fn items(lines: &[&str], language: &str) -> Vec<usize> {
+    let mut result = vec![0];
+    let mut scanner = Scanner::default();
+    let mut blank = false;
+
+    for (i, line) in lines.iter().enumerate() {
+        let top_level = scanner.depth == 0 && !scanner.block_comment;
+        let indented = line.starts_with(char::is_whitespace);
+        let closing = line.starts_with(['}', ')', ']']);
+
+        if i > 0 && blank && top_level && !indented && !closing && !line.trim().is_empty() {
+            result.push(i);
+        }
+
+        blank = line.trim().is_empty();
+        scanner.scan(line, language);
+    }
+
+    result
+}
+
+fn line_index(source: &Source, line: u32, lines_count: usize) -> usize {
+    let index = usize::try_from(line.saturating_sub(source.start_line)).unwrap_or_default();
+
+    index.min(lines_count - 1)
+}
+
+fn line_comment(language: &str) -> &'static str {
+    match language {
+        "Elixir" | "Perl" | "Python" | "Ruby" | "Shell" => "#",
+        "Haskell" | "Lua" | "SQL" => "--",
+        "Erlang" => "%",
+        _ => "//",
+    }
+}
+
+fn skip_string(chars: &[char], start: usize, quote: char) -> usize {
+    let mut i = start + 1;
+
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 1,
+            c if c == quote => return i,
+            _ => {}
+        }
+        i += 1;
+    }
+
+    i
+}
+
+fn split_lines(lines: &[&str], start: usize, end: usize, max_tokens: usize) -> Vec<(usize, usize)> {
+    let mut result = vec![];
+    let mut range_start = start;
+    let mut last_blank = None;
+    let mut size = 0;
+
+    for (i, line) in lines.iter().enumerate().take(end).skip(start) {
+        let line_tokens = token::estimate(line);
+
+        if size > 0 && size + line_tokens > max_tokens {
+            let split_at = last_blank.filter(|blank| *blank > range_start).unwrap_or(i);
+
+            result.push((range_start, split_at));
+            size = lines[split_at..i]
+                .iter()
+                .map(|line| token::estimate(line))
+                .sum();
+            range_start = split_at;
+            last_blank = None;
+        }
+
+        if line.trim().is_empty() {
+            last_blank = Some(i + 1);
+        }
+
+        size += line_tokens;
+    }
+
+    if range_start < end {
+        result.push((range_start, end));
+    }
+
+    result
+}
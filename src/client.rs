@@ -0,0 +1,249 @@
+use crate::{
+    Result, ask, backend, checker, chunk, commit_review, commit_summary,
+    config::Config,
+    convert_to_rust, criteria_verify, design_advice, doc_generate,
+    error::Error,
+    explain,
+    file::{self, Source},
+    finding,
+    mode::Mode,
+    output::{self, FileReport, Part},
+    performance, task_generate, task_review, test_generate,
+};
+use std::sync::Arc;
+
+#[derive(Clone, Debug)]
+pub struct Client {
+    config: Arc<Config>,
+    print: bool,
+}
+
+impl Client {
+    pub fn new(config: Config) -> Self {
+        Self {
+            config: Arc::new(config),
+            print: false,
+        }
+    }
+
+    pub fn config(&self) -> &Arc<Config> {
+        &self.config
+    }
+
+    pub fn print(mut self, print: bool) -> Self {
+        self.print = print;
+
+        self
+    }
+
+    pub fn with_mode(&self, mode: Mode) -> Self {
+        self.with_config(Config {
+            mode,
+            ..(*self.config).clone()
+        })
+    }
+
+    pub async fn ask(&self, question: &str) -> Result<FileReport> {
+        self.with_config(Config {
+            mode: Mode::Ask,
+            question: Some(question.to_string()),
+            ..(*self.config).clone()
+        })
+        .run_question()
+        .await
+    }
+
+    pub async fn check(&self, source: &Source) -> Result<FileReport> {
+        self.with_mode(Mode::Checker).run_file(source).await
+    }
+
+    pub async fn commit_review(&self, diff: &str) -> Result<FileReport> {
+        self.with_mode(Mode::CommitReview).run_diff(diff).await
+    }
+
+    pub async fn commit_summary(&self, diff: &str) -> Result<FileReport> {
+        self.with_mode(Mode::CommitSummary).run_diff(diff).await
+    }
+
+    pub async fn convert_to_rust(&self, source: &Source) -> Result<FileReport> {
+        self.with_mode(Mode::ConvertToRust).run_file(source).await
+    }
+
+    pub async fn criteria_verify(&self, diff: &str, criteria_file: &str) -> Result<FileReport> {
+        self.with_config(Config {
+            file: Some(criteria_file.to_string()),
+            mode: Mode::CriteriaVerify,
+            ..(*self.config).clone()
+        })
+        .run_diff(diff)
+        .await
+    }
+
+    pub async fn design_advice(&self, source: &Source) -> Result<FileReport> {
+        self.with_mode(Mode::DesignAdvice).run_file(source).await
+    }
+
+    pub async fn doc_generate(&self, source: &Source) -> Result<FileReport> {
+        self.with_mode(Mode::DocGenerate).run_file(source).await
+    }
+
+    pub async fn explain(&self, source: &Source) -> Result<FileReport> {
+        self.with_mode(Mode::Explain).run_file(source).await
+    }
+
+    pub async fn performance(&self, source: &Source) -> Result<FileReport> {
+        self.with_mode(Mode::Performance).run_file(source).await
+    }
+
+    pub async fn task_generate(&self, diff: &str) -> Result<FileReport> {
+        self.with_mode(Mode::TaskGenerate).run_diff(diff).await
+    }
+
+    pub async fn task_review(&self, diff: &str) -> Result<FileReport> {
+        self.with_mode(Mode::TaskReview).run_diff(diff).await
+    }
+
+    pub async fn test_generate(&self, source: &Source) -> Result<FileReport> {
+        self.with_mode(Mode::TestGenerate).run_file(source).await
+    }
+
+    pub async fn run_diff(&self, diff: &str) -> Result<FileReport> {
+        let config = self.config.clone();
+
+        let result = self
+            .output(async {
+                let result = match config.mode {
+                    Mode::CommitReview => commit_review::run(config.clone(), diff).await?,
+                    Mode::CommitSummary => commit_summary::run(config.clone(), diff).await?,
+                    Mode::CriteriaVerify => criteria_verify::run(config.clone(), diff).await?,
+                    Mode::TaskGenerate => task_generate::run(config.clone(), diff).await?,
+                    Mode::TaskReview => task_review::run(config.clone(), diff).await?,
+                    _ => return Err(Error::ModeNotSupported(config.mode.clone()).into()),
+                };
+
+                output::finished(&config, &result);
+
+                Ok(result)
+            })
+            .await?;
+
+        Ok(FileReport::new(&config.mode, None, vec![Part::new(result)]))
+    }
+
+    pub async fn run_file(&self, source: &Source) -> Result<FileReport> {
+        self.output(run_file(self.config.clone(), source)).await
+    }
+
+    pub async fn run_question(&self) -> Result<FileReport> {
+        let result = self
+            .output(async {
+                let result = ask::run(self.config.clone()).await?;
+
+                output::finished(&self.config, &result);
+
+                Ok(result)
+            })
+            .await?;
+
+        Ok(FileReport::new(&Mode::Ask, None, vec![Part::new(result)]))
+    }
+
+    async fn output<T>(&self, future: impl Future<Output = Result<T>>) -> Result<T> {
+        if self.print {
+            return future.await;
+        }
+
+        let (result, _) = output::capture(future).await;
+
+        result
+    }
+
+    fn with_config(&self, config: Config) -> Self {
+        Self {
+            config: Arc::new(config),
+            print: self.print,
+        }
+    }
+}
+
+async fn run_file(config: Arc<Config>, source: &Source) -> Result<FileReport> {
+    let context_length = backend::context_length(&config).await;
+    let max_tokens = chunk::max_tokens(&config, context_length)?;
+    let chunks = if source.changes.is_empty() {
+        chunk::split(source, max_tokens)
+    } else {
+        chunk::regions(source, config.diff_context, max_tokens)
+    };
+    let chunks_count = chunks.len();
+    let line_numbers = config.line_numbers
+        || (finding::has_findings(&config.mode)
+            && (chunks_count > 1 || !source.changes.is_empty()));
+    let mut parts = vec![];
+
+    for (i, mut chunk) in (1..).zip(chunks) {
+        let lines_count = u32::try_from(chunk.code.lines().count())?;
+        let last_line = chunk.start_line + lines_count.max(1) - 1;
+
+        if chunks_count > 1 || !source.changes.is_empty() {
+            output::progress(
+                &config,
+                &format!(
+                    "Part {i} of {chunks_count} lines {}-{last_line}",
+                    chunk.start_line
+                ),
+            );
+        }
+
+        if line_numbers && !matches!(config.mode, Mode::DocGenerate) {
+            chunk.code = file::number_lines(&chunk.code, chunk.start_line);
+
+            let context = match chunk.context {
+                Some(context) => format!("{context}\n\n{}", finding::LINE_NUMBERS_PROMPT),
+                None => finding::LINE_NUMBERS_PROMPT.to_string(),
+            };
+            chunk.context = Some(context);
+        }
+
+        let result = match config.mode {
+            Mode::Checker => checker::run(config.clone(), &chunk).await?,
+            Mode::ConvertToRust => convert_to_rust::run(config.clone(), &chunk).await?,
+            Mode::DesignAdvice => design_advice::run(config.clone(), &chunk).await?,
+            Mode::DocGenerate => doc_generate::run(config.clone(), &chunk).await?,
+            Mode::Explain => explain::run(config.clone(), &chunk).await?,
+            Mode::Performance => performance::run(config.clone(), &chunk).await?,
+            Mode::TestGenerate => test_generate::run(config.clone(), &chunk).await?,
+            _ => return Err(Error::ModeNotSupported(config.mode.clone()).into()),
+        };
+
+        output::finished(&config, &result);
+
+        parts.push(Part {
+            first_line: chunk.start_line,
+            last_line: Some(last_line),
+            line_numbers,
+            result,
+        });
+    }
+
+    let patch = match config.mode {
+        Mode::DocGenerate => Some(doc_generate::patch(&config, source, &parts)?),
+        _ => None,
+    };
+
+    let mut file_report = FileReport::new(&config.mode, Some(source.name.clone()), parts);
+    file_report.source = Some(source.clone());
+
+    if let Some(patch) = patch
+        && !file_report.skipped
+    {
+        output::print(&config, &patch);
+
+        file_report.text = Some(patch);
+    }
+
+    if config.line_numbers {
+        output::locations(&config, &file_report);
+    }
+
+    Ok(file_report)
+}
@@ -1,7 +1,9 @@
 use crate::{
     Result,
+    backend::{self, Message},
     config::Config,
-    ollama::{self, Message},
+    mode::ModeResult,
+    prompt::{self, Vars},
 };
 use chrono::Utc;
 use std::sync::Arc;
@@ -71,10 +73,13 @@ Output Rules:
 Problem summary
 (A short, precise title)
 
+Severity: critical | high | medium | low
+(Exactly one of these four words)
+
 Problem detailed description
 - Why this is a real issue based on the diff
 - When and how it could manifest
-- Potential impact or severity
+- Potential impact
 - The specific part of the diff that demonstrates it
 
 Recommendation
@@ -84,25 +89,27 @@ Recommendation
 5. If there are no meaningful findings, output exactly:
 “The code looks OK.”";
 
-pub async fn run(config: Arc<Config>, code: &str) -> Result<()> {
+pub const USER_TEMPLATE: &str = "Here is the code: {code}";
+
+pub async fn run(config: Arc<Config>, code: &str) -> Result<ModeResult> {
     let start_date = Utc::now();
 
+    let vars = Vars::new(&config, code);
+
     let mut messages = vec![];
 
     let message = Message {
-        content: SYSTEM_PROMPT.to_string(),
+        content: prompt::system(&config, SYSTEM_PROMPT)?,
         role: "system".to_string(),
     };
     messages.push(message);
 
-    let prompt = format!("Here is the code: {code}");
+    let prompt = prompt::user(&config, USER_TEMPLATE, &vars)?;
     let message = Message {
         content: prompt.clone(),
         role: "user".to_string(),
     };
     messages.push(message);
 
-    ollama::run_request(config, messages, start_date).await?;
-
-    Ok(())
+    backend::run_request(config, messages, start_date).await
 }
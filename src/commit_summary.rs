@@ -1,7 +1,9 @@
 use crate::{
     Result,
+    backend::{self, Message},
     config::Config,
-    ollama::{self, Message},
+    mode::ModeResult,
+    prompt::{self, Vars},
 };
 use chrono::Utc;
 use std::sync::Arc;
@@ -39,25 +41,27 @@ Your responsibilities:
    - Do not include explanations, meta-comments, or anything else.
 5. If the diff shows no meaningful change, still produce one valid commit title and summary describing that no code changes occurred.";
 
-pub async fn run(config: Arc<Config>, code: &str) -> Result<()> {
+pub const USER_TEMPLATE: &str = "Here is the code: {code}";
+
+pub async fn run(config: Arc<Config>, code: &str) -> Result<ModeResult> {
     let start_date = Utc::now();
 
+    let vars = Vars::new(&config, code);
+
     let mut messages = vec![];
 
     let message = Message {
-        content: SYSTEM_PROMPT.to_string(),
+        content: prompt::system(&config, SYSTEM_PROMPT)?,
         role: "system".to_string(),
     };
     messages.push(message);
 
-    let prompt = format!("Here is the code: {code}");
+    let prompt = prompt::user(&config, USER_TEMPLATE, &vars)?;
     let message = Message {
         content: prompt.clone(),
         role: "user".to_string(),
     };
     messages.push(message);
 
-    ollama::run_request(config, messages, start_date).await?;
-
-    Ok(())
+    backend::run_request(config, messages, start_date).await
 }
@@ -1,92 +1,318 @@
 use crate::{
     Result,
-    app::{Args, Mode},
+    app::Args,
+    backend::{Backend, DEFAULT_CODE_NUM_CTX},
+    cache::DEFAULT_CACHE_MAX_SIZE,
+    config_file,
+    diff::DIFF_CONTEXT_LINES,
     error::Error,
+    finding::Severity,
+    language::Language,
+    mode::Mode,
+    output::Format,
 };
 use std::str::FromStr;
 
+pub const DEFAULT_OLLAMA_HOST: &str = "http://localhost:11434";
+
 #[derive(Clone, Debug)]
 pub struct Config {
+    pub api_key: Option<String>,
+    pub backend: Backend,
+    pub cache: bool,
+    pub cache_max_size: u64,
+    pub chunk_size: u32,
+    pub commit: Option<String>,
+    pub diff: bool,
+    pub diff_context: u32,
     pub dir: Option<String>,
     pub end_line: Option<u32>,
+    pub exclude: Vec<String>,
+    pub fail_on: Option<Severity>,
     pub file: Option<String>,
+    pub format: Format,
+    pub host: String,
+    pub include: Vec<String>,
+    pub jobs: usize,
     pub keep_alive: u16,
+    pub languages: Vec<Language>,
+    pub line_numbers: bool,
     pub max_attempts: u8,
     pub mode: Mode,
     pub model: Option<String>,
-    pub ollama_host: String,
+    pub output: Option<String>,
+    pub output_dir: Option<String>,
+    pub prompt_template: Option<String>,
     pub question: Option<String>,
+    pub range: Option<String>,
     pub skip_larger: Option<u32>,
+    pub staged: bool,
+    pub stall_timeout: u64,
     pub start_line: Option<u32>,
+    pub stream: bool,
+    pub system_prompt: Option<String>,
+    pub system_prompt_append: Option<String>,
     pub timeout: u64,
+    pub write_tests: bool,
 }
 
 impl Config {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
+        api_key: Option<String>,
+        backend: Backend,
+        cache: bool,
+        cache_max_size: u64,
+        chunk_size: u32,
+        commit: Option<String>,
+        diff: bool,
+        diff_context: u32,
         dir: Option<String>,
         end_line: Option<u32>,
+        exclude: Vec<String>,
+        fail_on: Option<Severity>,
         file: Option<String>,
+        format: Format,
+        host: String,
+        include: Vec<String>,
+        jobs: usize,
         keep_alive: u16,
+        languages: Vec<Language>,
+        line_numbers: bool,
         max_attempts: u8,
         mode: Mode,
         model: Option<String>,
-        ollama_host: String,
+        output: Option<String>,
+        output_dir: Option<String>,
+        prompt_template: Option<String>,
         question: Option<String>,
+        range: Option<String>,
         skip_larger: Option<u32>,
+        staged: bool,
+        stall_timeout: u64,
         start_line: Option<u32>,
+        stream: bool,
+        system_prompt: Option<String>,
+        system_prompt_append: Option<String>,
         timeout: u64,
+        write_tests: bool,
     ) -> Self {
         Self {
+            api_key,
+            backend,
+            cache,
+            cache_max_size,
+            chunk_size,
+            commit,
+            diff,
+            diff_context,
             dir,
             end_line,
+            exclude,
+            fail_on,
             file,
+            format,
+            host,
+            include,
+            jobs,
             keep_alive,
+            languages,
+            line_numbers,
             max_attempts,
             mode,
             model,
-            ollama_host,
+            output,
+            output_dir,
+            prompt_template,
             question,
+            range,
             skip_larger,
+            staged,
+            stall_timeout,
             start_line,
+            stream,
+            system_prompt,
+            system_prompt_append,
             timeout,
+            write_tests,
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            api_key: None,
+            backend: Backend::Ollama,
+            cache: true,
+            cache_max_size: DEFAULT_CACHE_MAX_SIZE,
+            chunk_size: DEFAULT_CODE_NUM_CTX,
+            commit: None,
+            diff: false,
+            diff_context: DIFF_CONTEXT_LINES,
+            dir: None,
+            end_line: None,
+            exclude: vec![],
+            fail_on: None,
+            file: None,
+            format: Format::Text,
+            host: DEFAULT_OLLAMA_HOST.to_string(),
+            include: vec![],
+            jobs: 1,
+            keep_alive: 0,
+            languages: vec![],
+            line_numbers: false,
+            max_attempts: 3,
+            mode: Mode::Checker,
+            model: None,
+            output: None,
+            output_dir: None,
+            prompt_template: None,
+            question: None,
+            range: None,
+            skip_larger: None,
+            staged: false,
+            stall_timeout: 60,
+            start_line: None,
+            stream: true,
+            system_prompt: None,
+            system_prompt_append: None,
+            timeout: 300,
+            write_tests: false,
         }
     }
 }
 
 pub fn load(args: Args) -> Result<Config> {
+    let config_file = config_file::load()?;
+    let mode = if let Some(mode) = args.mode.or(config_file.mode.clone()) {
+        Mode::from_str(&mode)?
+    } else {
+        Mode::Checker
+    };
+    let config_file = config_file.for_mode(&mode.to_string());
+
+    let api_key = std::env::var("OPENAI_API_KEY").ok();
+    let backend = if let Some(backend) = args.backend.or(config_file.backend) {
+        Backend::from_str(&backend)?
+    } else {
+        Backend::Ollama
+    };
+    let cache = !args.no_cache && config_file.cache.unwrap_or(true);
+    let cache_max_size = args
+        .cache_max_size
+        .or(config_file.cache_max_size)
+        .unwrap_or(DEFAULT_CACHE_MAX_SIZE);
+    let chunk_size = args
+        .chunk_size
+        .or(config_file.chunk_size)
+        .unwrap_or(DEFAULT_CODE_NUM_CTX);
+    let commit = args.commit;
+    let diff = args.diff;
+    let diff_context = args
+        .diff_context
+        .or(config_file.diff_context)
+        .unwrap_or(DIFF_CONTEXT_LINES);
     let dir = args.dir;
     let end_line = args.end_line;
+    let mut exclude = config_file.exclude.unwrap_or_default();
+    exclude.extend(args.exclude);
+    let fail_on = if let Some(fail_on) = args.fail_on.or(config_file.fail_on) {
+        Some(Severity::from_str(&fail_on)?)
+    } else {
+        None
+    };
     let file = args.file;
-    let keep_alive = args.keep_alive.unwrap_or(0);
-    let max_attempts = args.max_attempts.unwrap_or(3);
-    let mode = if let Some(mode) = args.mode {
-        Mode::from_str(&mode)?
+    let format = if let Some(format) = args.format.or(config_file.format) {
+        Format::from_str(&format)?
     } else {
-        Mode::Checker
+        Format::Text
     };
-    let model = args.model;
-    let Ok(ollama_host) = std::env::var("OLLAMA_HOST") else {
-        return Err(Box::new(Error::OllamaHostAddresMissing));
+    let host = match backend {
+        Backend::Ollama => {
+            let Ok(ollama_host) = std::env::var("OLLAMA_HOST") else {
+                return Err(Box::new(Error::OllamaHostAddresMissing));
+            };
+
+            ollama_host
+        }
+        Backend::OpenAi => {
+            let Ok(openai_base_url) = std::env::var("OPENAI_BASE_URL") else {
+                return Err(Box::new(Error::OpenAiBaseUrlMissing));
+            };
+
+            openai_base_url
+        }
     };
+    let mut include = config_file.include.unwrap_or_default();
+    include.extend(args.include);
+    let jobs = args.jobs.or(config_file.jobs).unwrap_or(1).max(1);
+    let keep_alive = args.keep_alive.or(config_file.keep_alive).unwrap_or(0);
+    let languages = config_file.languages;
+    let line_numbers = args
+        .line_numbers
+        .or(config_file.line_numbers)
+        .unwrap_or(false);
+    let max_attempts = args.max_attempts.or(config_file.max_attempts).unwrap_or(3);
+    let model = args.model.or(config_file.model);
+    let output = args.output;
+    let output_dir = args.output_dir;
+    let prompt_template = args.prompt_template.or(config_file.prompt_template);
     let question = args.question;
-    let skip_larger = args.skip_larger;
+    let range = args.range;
+    let skip_larger = args.skip_larger.or(config_file.skip_larger);
+    let staged = args.staged;
+    let stall_timeout = args
+        .stall_timeout
+        .or(config_file.stall_timeout)
+        .unwrap_or(60);
     let start_line = args.start_line;
-    let timeout = args.timeout.unwrap_or(300);
+    let stream = args.stream.or(config_file.stream).unwrap_or(true);
+    let system_prompt = args.system_prompt.or(config_file.system_prompt);
+    let system_prompt_append = args
+        .system_prompt_append
+        .or(config_file.system_prompt_append);
+    let timeout = args.timeout.or(config_file.timeout).unwrap_or(300);
+    let write_tests = args.write_tests;
 
     let config = Config::new(
+        api_key,
+        backend,
+        cache,
+        cache_max_size,
+        chunk_size,
+        commit,
+        diff,
+        diff_context,
         dir,
         end_line,
+        exclude,
+        fail_on,
         file,
+        format,
+        host,
+        include,
+        jobs,
         keep_alive,
+        languages,
+        line_numbers,
         max_attempts,
         mode,
         model,
-        ollama_host,
+        output,
+        output_dir,
+        prompt_template,
         question,
+        range,
         skip_larger,
+        staged,
+        stall_timeout,
         start_line,
+        stream,
+        system_prompt,
+        system_prompt_append,
         timeout,
+        write_tests,
     );
 
     Ok(config)
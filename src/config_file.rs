@@ -0,0 +1,144 @@
+use crate::{Result, error::Error, language::Language};
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    env,
+    fs::read_to_string,
+    path::{Path, PathBuf},
+};
+
+pub const GLOBAL_CONFIG_FILE: &str = "ccw/config.toml";
+pub const LOCAL_CONFIG_FILE: &str = ".ccw.toml";
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    pub backend: Option<String>,
+    pub cache: Option<bool>,
+    pub cache_max_size: Option<u64>,
+    pub chunk_size: Option<u32>,
+    pub diff_context: Option<u32>,
+    pub exclude: Option<Vec<String>>,
+    pub fail_on: Option<String>,
+    pub format: Option<String>,
+    pub include: Option<Vec<String>>,
+    pub jobs: Option<usize>,
+    pub keep_alive: Option<u16>,
+    #[serde(default)]
+    pub languages: Vec<Language>,
+    pub line_numbers: Option<bool>,
+    pub max_attempts: Option<u8>,
+    pub mode: Option<String>,
+    #[serde(default)]
+    pub modes: HashMap<String, ConfigFile>,
+    pub model: Option<String>,
+    pub prompt_template: Option<String>,
+    pub skip_larger: Option<u32>,
+    pub stall_timeout: Option<u64>,
+    pub stream: Option<bool>,
+    pub system_prompt: Option<String>,
+    pub system_prompt_append: Option<String>,
+    pub timeout: Option<u64>,
+}
+
+impl ConfigFile {
+    pub fn for_mode(mut self, mode: &str) -> Self {
+        let Some(mode_config) = self.modes.remove(mode) else {
+            return self;
+        };
+
+        self.merge(mode_config)
+    }
+
+    pub fn merge(self, other: ConfigFile) -> Self {
+        let mut languages = other.languages;
+        languages.extend(self.languages);
+
+        let mut modes = self.modes;
+
+        for (mode, mode_config) in other.modes {
+            let merged = match modes.remove(&mode) {
+                Some(existing) => existing.merge(mode_config),
+                None => mode_config,
+            };
+            modes.insert(mode, merged);
+        }
+
+        Self {
+            backend: other.backend.or(self.backend),
+            cache: other.cache.or(self.cache),
+            cache_max_size: other.cache_max_size.or(self.cache_max_size),
+            chunk_size: other.chunk_size.or(self.chunk_size),
+            diff_context: other.diff_context.or(self.diff_context),
+            exclude: other.exclude.or(self.exclude),
+            fail_on: other.fail_on.or(self.fail_on),
+            format: other.format.or(self.format),
+            include: other.include.or(self.include),
+            jobs: other.jobs.or(self.jobs),
+            keep_alive: other.keep_alive.or(self.keep_alive),
+            languages,
+            line_numbers: other.line_numbers.or(self.line_numbers),
+            max_attempts: other.max_attempts.or(self.max_attempts),
+            mode: other.mode.or(self.mode),
+            modes,
+            model: other.model.or(self.model),
+            prompt_template: other.prompt_template.or(self.prompt_template),
+            skip_larger: other.skip_larger.or(self.skip_larger),
+            stall_timeout: other.stall_timeout.or(self.stall_timeout),
+            stream: other.stream.or(self.stream),
+            system_prompt: other.system_prompt.or(self.system_prompt),
+            system_prompt_append: other.system_prompt_append.or(self.system_prompt_append),
+            timeout: other.timeout.or(self.timeout),
+        }
+    }
+}
+
+pub fn global_path() -> Option<PathBuf> {
+    if let Ok(config_home) = env::var("XDG_CONFIG_HOME")
+        && !config_home.is_empty()
+    {
+        return Some(Path::new(&config_home).join(GLOBAL_CONFIG_FILE));
+    }
+
+    let home = env::var("HOME").ok()?;
+
+    Some(Path::new(&home).join(".config").join(GLOBAL_CONFIG_FILE))
+}
+
+pub fn local_path() -> Option<PathBuf> {
+    let current_dir = env::current_dir().ok()?;
+
+    for dir in current_dir.ancestors() {
+        let path = dir.join(LOCAL_CONFIG_FILE);
+
+        if path.is_file() {
+            return Some(path);
+        }
+    }
+
+    None
+}
+
+pub fn load() -> Result<ConfigFile> {
+    let mut result = ConfigFile::default();
+
+    for path in [global_path(), local_path()].into_iter().flatten() {
+        if path.is_file() {
+            result = result.merge(read(&path)?);
+        }
+    }
+
+    Ok(result)
+}
+
+pub fn read(path: &Path) -> Result<ConfigFile> {
+    let content = read_to_string(path)?;
+
+    match toml::from_str(&content) {
+        Err(e) => Err(Box::new(Error::ConfigFileInvalid {
+            message: e.to_string(),
+            path: path.display().to_string(),
+        })),
+        Ok(config_file) => Ok(config_file),
+    }
+}
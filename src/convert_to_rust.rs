@@ -1,7 +1,10 @@
 use crate::{
     Result,
+    backend::{self, Message},
     config::Config,
-    ollama::{self, Message},
+    file::Source,
+    mode::ModeResult,
+    prompt::{self, Vars},
 };
 use chrono::Utc;
 use std::sync::Arc;
@@ -45,25 +48,39 @@ Your output must follow these rules:
 
 Your goal is to deliver a clean, accurate, idiomatic Rust version of the provided code.";
 
-pub async fn run(config: Arc<Config>, code: &str) -> Result<()> {
+pub const USER_TEMPLATE: &str = "Here is the {language} code: {code}";
+
+pub async fn run(config: Arc<Config>, source: &Source) -> Result<ModeResult> {
     let start_date = Utc::now();
 
+    let vars = Vars {
+        file_name: &source.name,
+        language: &source.language,
+        ..Vars::new(&config, &source.code)
+    };
+
     let mut messages = vec![];
 
     let message = Message {
-        content: SYSTEM_PROMPT.to_string(),
+        content: prompt::system(&config, SYSTEM_PROMPT)?,
         role: "system".to_string(),
     };
     messages.push(message);
 
-    let prompt = format!("Here is the code: {code}");
+    if let Some(context) = &source.context {
+        let message = Message {
+            content: context.clone(),
+            role: "user".to_string(),
+        };
+        messages.push(message);
+    }
+
+    let prompt = prompt::user(&config, USER_TEMPLATE, &vars)?;
     let message = Message {
         content: prompt.clone(),
         role: "user".to_string(),
     };
     messages.push(message);
 
-    ollama::run_request(config, messages, start_date).await?;
-
-    Ok(())
+    backend::run_request(config, messages, start_date).await
 }
@@ -1,8 +1,11 @@
 use crate::{
     Result,
+    backend::{self, Message},
     config::Config,
+    error::Error,
     file::read,
-    ollama::{self, Message},
+    mode::ModeResult,
+    prompt::{self, Vars},
 };
 use chrono::Utc;
 use std::sync::Arc;
@@ -43,18 +46,20 @@ Follow these rules:
 
 Your goal is to deliver a strict, objective, diff-based assessment of whether the code changes fulfill the acceptance criteria.";
 
-pub async fn run(config: Arc<Config>, code: &str) -> Result<()> {
+pub const USER_TEMPLATE: &str = "Here is the code: {code}";
+
+pub async fn run(config: Arc<Config>, code: &str) -> Result<ModeResult> {
     let start_date = Utc::now();
 
     let mut messages = vec![];
 
     let message = Message {
-        content: SYSTEM_PROMPT.to_string(),
+        content: prompt::system(&config, SYSTEM_PROMPT)?,
         role: "system".to_string(),
     };
     messages.push(message);
 
-    if let Some(file) = &config.file {
+    let criteria_content = if let Some(file) = &config.file {
         let criteria_content = read(&config, file)?;
 
         let content = format!("Here are the acceptance criteria: {criteria_content}");
@@ -64,20 +69,23 @@ pub async fn run(config: Arc<Config>, code: &str) -> Result<()> {
             role: "system".to_string(),
         };
         messages.push(message);
+
+        criteria_content
     } else {
-        println!("You need to provide input --file with acceptance criteria.");
+        return Err(Box::new(Error::CriteriaFileMissing));
+    };
 
-        return Ok(());
-    }
+    let vars = Vars {
+        criteria: &criteria_content,
+        ..Vars::new(&config, code)
+    };
 
-    let prompt = format!("Here is the code: {code}");
+    let prompt = prompt::user(&config, USER_TEMPLATE, &vars)?;
     let message = Message {
         content: prompt.clone(),
         role: "user".to_string(),
     };
     messages.push(message);
 
-    ollama::run_request(config, messages, start_date).await?;
-
-    Ok(())
+    backend::run_request(config, messages, start_date).await
 }
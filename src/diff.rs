@@ -0,0 +1,207 @@
+use crate::{
+    Result,
+    config::Config,
+    error::Error,
+    file::{self, Source},
+    language::Registry,
+    output,
+};
+use std::{
+    env,
+    fs::read_to_string,
+    path::{Component, Path, PathBuf},
+};
+
+pub const DIFF_CONTEXT_LINES: u32 = 3;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FileDiff {
+    pub changes: Vec<(u32, u32)>,
+    pub path: String,
+}
+
+pub fn parse(diff: &str) -> Vec<FileDiff> {
+    let mut result = vec![];
+    let mut current: Option<(String, Vec<u32>)> = None;
+    let mut line_number = 0;
+    let mut old_left = 0;
+    let mut new_left = 0;
+
+    for line in diff.lines() {
+        if old_left == 0 && new_left == 0 {
+            if let Some(path) = line.strip_prefix("+++ ") {
+                if let Some((path, lines)) = current.take() {
+                    result.push(file_diff(path, lines));
+                }
+
+                let path = path.split('\t').next().unwrap_or_default().trim_end();
+
+                current = (path != "/dev/null")
+                    .then(|| (path.strip_prefix("b/").unwrap_or(path).to_string(), vec![]));
+            } else if let Some(header) = line.strip_prefix("@@ ")
+                && let Some((old_count, new_start, new_count)) = parse_hunk_header(header)
+            {
+                line_number = new_start;
+                old_left = old_count;
+                new_left = new_count;
+            }
+
+            continue;
+        }
+
+        match line.chars().next() {
+            Some('+') => {
+                if let Some((_, lines)) = &mut current {
+                    lines.push(line_number);
+                }
+                line_number += 1;
+                new_left = new_left.saturating_sub(1);
+            }
+            Some('-') => {
+                if let Some((_, lines)) = &mut current {
+                    lines.push(line_number.max(1));
+                }
+                old_left = old_left.saturating_sub(1);
+            }
+            Some('\\') => {}
+            _ => {
+                line_number += 1;
+                old_left = old_left.saturating_sub(1);
+                new_left = new_left.saturating_sub(1);
+            }
+        }
+    }
+
+    if let Some((path, lines)) = current {
+        result.push(file_diff(path, lines));
+    }
+
+    result.retain(|file_diff| !file_diff.changes.is_empty());
+
+    result
+}
+
+pub fn sources(config: &Config, diff: &str, root: Option<&Path>) -> Result<Vec<Source>> {
+    let mut result = vec![];
+    let mut missing = 0;
+
+    let registry = Registry::new(&config.languages);
+    let include = file::glob_set(&config.include)?;
+    let exclude = file::glob_set(&config.exclude)?;
+    let dir = config
+        .dir
+        .as_deref()
+        .map(|dir| dir.trim_start_matches("./"))
+        .filter(|dir| !dir.is_empty() && *dir != ".");
+    let working_dir = root.and_then(working_dir);
+
+    for file_diff in parse(diff) {
+        let name = match &working_dir {
+            Some(working_dir) => relative_path(&file_diff.path, working_dir),
+            None => file_diff.path,
+        };
+        let path = Path::new(&name);
+
+        if dir.is_some_and(|dir| !path.starts_with(dir))
+            || !file::is_included(
+                path,
+                Path::new(dir.unwrap_or(".")),
+                &include,
+                &exclude,
+                config.include.is_empty(),
+            )
+        {
+            continue;
+        }
+
+        let Some(language) = registry.detect(path) else {
+            continue;
+        };
+
+        let Ok(code) = read_to_string(path) else {
+            output::warn(
+                config,
+                &format!("Skipping {name}: file not found in the working tree."),
+            );
+            missing += 1;
+
+            continue;
+        };
+
+        result.push(Source {
+            changes: file_diff.changes,
+            code,
+            context: None,
+            language: language.to_string(),
+            name,
+            start_line: 1,
+        });
+    }
+
+    if result.is_empty() && missing > 0 {
+        return Err(Box::new(Error::ChangedFilesMissing(missing)));
+    }
+
+    Ok(result)
+}
+
+fn file_diff(path: String, mut lines: Vec<u32>) -> FileDiff {
+    lines.sort_unstable();
+    lines.dedup();
+
+    let mut changes: Vec<(u32, u32)> = vec![];
+
+    for line in lines {
+        match changes.last_mut() {
+            Some(last) if line <= last.1 + 1 => last.1 = line,
+            _ => changes.push((line, line)),
+        }
+    }
+
+    FileDiff { changes, path }
+}
+
+fn relative_path(path: &str, working_dir: &Path) -> String {
+    let path = Path::new(path);
+
+    if let Ok(path) = path.strip_prefix(working_dir) {
+        return path.to_string_lossy().to_string();
+    }
+
+    let mut result = PathBuf::new();
+
+    for _ in working_dir.components() {
+        result.push(Component::ParentDir);
+    }
+
+    result.join(path).to_string_lossy().to_string()
+}
+
+fn working_dir(root: &Path) -> Option<PathBuf> {
+    let current_dir = env::current_dir().ok()?.canonicalize().ok()?;
+    let root = root.canonicalize().ok()?;
+
+    current_dir
+        .strip_prefix(root)
+        .ok()
+        .filter(|working_dir| !working_dir.as_os_str().is_empty())
+        .map(Path::to_path_buf)
+}
+
+fn parse_hunk_header(header: &str) -> Option<(u32, u32, u32)> {
+    let mut ranges = header.split_whitespace();
+    let old = ranges.next()?.strip_prefix('-')?;
+    let new = ranges.next()?.strip_prefix('+')?;
+
+    let count = |range: &str| -> Option<(u32, u32)> {
+        match range.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((range.parse().ok()?, 1)),
+        }
+    };
+
+    let (_, old_count) = count(old)?;
+    let (new_start, new_count) = count(new)?;
+
+    Some((old_count, new_start, new_count))
+}
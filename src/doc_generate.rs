@@ -0,0 +1,386 @@
+use crate::{
+    Result,
+    backend::{self, Message},
+    config::Config,
+    diff::DIFF_CONTEXT_LINES,
+    file::Source,
+    mode::ModeResult,
+    output::{self, Part},
+    prompt::{self, Vars},
+    test_generate,
+};
+use chrono::Utc;
+use std::{fs::read_to_string, sync::Arc};
+
+pub const ANCHOR_LOOKAHEAD: usize = 200;
+pub const MAX_TABLE_CELLS: usize = 4 * 1024 * 1024;
+
+pub const SYSTEM_PROMPT: &str = "You are CCW-DOC-GENERATE, a careful technical writer. Your role is to add documentation comments to the code provided by the user.
+
+The user will provide:
+- A source file, or a fragment of one, and its language.
+
+Your output must follow these rules:
+
+1. Document only public items that have no documentation yet.
+   - Public functions, methods, types, traits, interfaces, classes, constants and modules.
+   - Leave private items and items that already have a doc comment as they are.
+
+2. Use the idiomatic documentation style of the language.
+   - Rust: `///` comments placed before the item and its attributes.
+   - Python: docstrings as the first statement of the module, class or function body, indented like the body.
+   - Java and Kotlin: Javadoc/KDoc `/** ... */` blocks with `@param`, `@return` and `@throws` where relevant.
+   - JavaScript/TypeScript: JSDoc `/** ... */` blocks with `@param` and `@returns`.
+   - Go: `//` comments that start with the name of the item.
+   - C#: `///` XML documentation comments.
+   - Other languages: the documentation comment style of the ecosystem.
+
+3. Keep every existing line exactly as it is.
+   - Do not change, reformat, reorder, rename or remove any line of code or any existing comment.
+   - Only insert new documentation comment lines.
+   - Do not add comments inside function bodies, except Python docstrings.
+
+4. Write useful documentation.
+   - Describe what the item does, its parameters, return value and errors, based only on the code.
+   - Be concise: one summary sentence, followed by details only when they help.
+   - Do not invent behavior that is not visible in the code.
+
+5. Output the complete code with the added documentation.
+   - Put the whole code in ONE fenced code block.
+   - No explanations before or after the code block.
+
+Your goal is to deliver the same code, unchanged, with clear documentation for its public items.";
+
+pub const USER_TEMPLATE: &str = "Here is the {language} code from {file_name}: {code}";
+
+#[derive(Clone, Debug)]
+struct CommentStyle {
+    block: Option<(&'static str, &'static str)>,
+    docstrings: &'static [&'static str],
+    lines: &'static [&'static str],
+}
+
+impl CommentStyle {
+    fn new(language: &str) -> Self {
+        let (block, docstrings, lines): (_, &'static [&'static str], &'static [&'static str]) =
+            match language {
+                "Elixir" => (None, &["\"\"\"", "\""], &["#"]),
+                "Erlang" => (None, &[], &["%"]),
+                "Haskell" => (Some(("{-", "-}")), &[], &["--"]),
+                "Lua" => (Some(("--[[", "]]")), &[], &["--"]),
+                "Perl" | "Ruby" | "Shell" => (None, &[], &["#"]),
+                "PHP" => (Some(("/*", "*/")), &[], &["//", "#"]),
+                "Python" => (None, &["\"\"\"", "'''"], &["#"]),
+                "SQL" => (Some(("/*", "*/")), &[], &["--"]),
+                _ => (Some(("/*", "*/")), &[], &["//"]),
+            };
+
+        Self {
+            block,
+            docstrings,
+            lines,
+        }
+    }
+
+    fn is_documentation(&self, lines: &[String]) -> bool {
+        let mut closing: Option<&str> = None;
+
+        'lines: for line in lines {
+            let line = line.trim();
+
+            if let Some(end) = closing {
+                if line.contains(end) {
+                    closing = None;
+                }
+
+                continue;
+            }
+
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some((start, end)) = self.block
+                && let Some(rest) = line.strip_prefix(start)
+            {
+                if !rest.contains(end) {
+                    closing = Some(end);
+                }
+
+                continue;
+            }
+
+            if self.lines.iter().any(|prefix| line.starts_with(prefix)) {
+                continue;
+            }
+
+            let text = line
+                .strip_prefix("@moduledoc")
+                .or_else(|| line.strip_prefix("@doc"))
+                .map_or(line, str::trim_start);
+            let text = text.strip_prefix(['r', 'R']).unwrap_or(text);
+
+            for quote in self.docstrings {
+                if let Some(rest) = text.strip_prefix(quote) {
+                    if !rest.contains(quote) {
+                        closing = Some(quote);
+                    }
+
+                    continue 'lines;
+                }
+            }
+
+            return false;
+        }
+
+        closing.is_none() && lines.iter().any(|line| !line.trim().is_empty())
+    }
+}
+
+pub async fn run(config: Arc<Config>, source: &Source) -> Result<ModeResult> {
+    let start_date = Utc::now();
+
+    let vars = Vars {
+        file_name: &source.name,
+        language: &source.language,
+        ..Vars::new(&config, &source.code)
+    };
+
+    let mut messages = vec![];
+
+    let message = Message {
+        content: prompt::system(&config, SYSTEM_PROMPT)?,
+        role: "system".to_string(),
+    };
+    messages.push(message);
+
+    if let Some(context) = &source.context {
+        let message = Message {
+            content: context.clone(),
+            role: "user".to_string(),
+        };
+        messages.push(message);
+    }
+
+    let prompt = prompt::user(&config, USER_TEMPLATE, &vars)?;
+    let message = Message {
+        content: prompt.clone(),
+        role: "user".to_string(),
+    };
+    messages.push(message);
+
+    backend::run_request(config, messages, start_date).await
+}
+
+pub fn insertions(original: &[&str], documented: &[&str]) -> Vec<(usize, Vec<String>)> {
+    let prefix = original
+        .iter()
+        .zip(documented)
+        .take_while(|(a, b)| a.trim() == b.trim())
+        .count();
+    let suffix = original[prefix..]
+        .iter()
+        .rev()
+        .zip(documented[prefix..].iter().rev())
+        .take_while(|(a, b)| a.trim() == b.trim())
+        .count();
+
+    let original = &original[prefix..original.len() - suffix];
+    let documented = &documented[prefix..documented.len() - suffix];
+
+    let cells = (original.len() + 1).saturating_mul(documented.len() + 1);
+
+    let result = if cells <= MAX_TABLE_CELLS {
+        common_subsequence(original, documented)
+    } else {
+        anchored(original, documented)
+    };
+
+    result
+        .into_iter()
+        .map(|(position, lines)| (prefix + position, lines))
+        .collect()
+}
+
+pub fn patch(config: &Config, source: &Source, parts: &[Part]) -> Result<String> {
+    let (content, first_line) = match read_to_string(&source.name) {
+        Ok(content) => (content, 1),
+        Err(_) => (source.code.clone(), source.start_line),
+    };
+    let lines = content.lines().collect::<Vec<&str>>();
+    let style = CommentStyle::new(&source.language);
+
+    let mut accepted = vec![];
+    let mut ignored = 0;
+
+    for part in parts {
+        let Some(code) = part
+            .result
+            .text
+            .as_deref()
+            .and_then(test_generate::code_block)
+        else {
+            continue;
+        };
+
+        let start = usize::try_from(part.first_line.saturating_sub(first_line))?.min(lines.len());
+        let end = match part.last_line {
+            Some(last_line) => usize::try_from((last_line + 1).saturating_sub(first_line))?,
+            None => lines.len(),
+        }
+        .clamp(start, lines.len());
+
+        let documented = code.lines().collect::<Vec<&str>>();
+
+        for (position, inserted) in insertions(&lines[start..end], &documented) {
+            let position = start + position;
+
+            if position < lines.len() && style.is_documentation(&inserted) {
+                accepted.push((position, inserted));
+            } else if inserted.iter().any(|line| !line.trim().is_empty()) {
+                ignored += inserted.len();
+            }
+        }
+    }
+
+    if ignored > 0 {
+        output::progress(
+            config,
+            &format!(
+                "Ignored {ignored} lines of {} that would have changed the code.",
+                source.name
+            ),
+        );
+    }
+
+    Ok(unified_diff(&source.name, &content, first_line, &accepted))
+}
+
+pub fn unified_diff(
+    file_name: &str,
+    content: &str,
+    first_line: u32,
+    insertions: &[(usize, Vec<String>)],
+) -> String {
+    if insertions.is_empty() {
+        return String::new();
+    }
+
+    let lines = content.lines().collect::<Vec<&str>>();
+    let context = usize::try_from(DIFF_CONTEXT_LINES).unwrap_or_default();
+    let first_line = usize::try_from(first_line).unwrap_or(1);
+    let path = file_name.strip_prefix("./").unwrap_or(file_name);
+
+    let mut hunks: Vec<&[(usize, Vec<String>)]> = vec![];
+    let mut start = 0;
+
+    for i in 1..=insertions.len() {
+        if i == insertions.len() || insertions[i].0 - insertions[i - 1].0 > 2 * context {
+            hunks.push(&insertions[start..i]);
+            start = i;
+        }
+    }
+
+    let mut result = format!("--- a/{path}\n+++ b/{path}\n");
+    let mut offset = 0;
+
+    for hunk in hunks {
+        let old_start = hunk[0].0.saturating_sub(context);
+        let old_end = (hunk[hunk.len() - 1].0 + context).min(lines.len());
+        let added = hunk.iter().map(|(_, lines)| lines.len()).sum::<usize>();
+        let old_count = old_end - old_start;
+
+        result.push_str(&format!(
+            "@@ -{},{old_count} +{},{} @@\n",
+            old_start + first_line,
+            old_start + first_line + offset,
+            old_count + added
+        ));
+
+        let mut inserted = hunk.iter().peekable();
+
+        for (i, line) in lines.iter().enumerate().take(old_end).skip(old_start) {
+            while let Some((_, lines)) = inserted.next_if(|(position, _)| *position == i) {
+                for line in lines {
+                    result.push_str(&format!("+{line}\n"));
+                }
+            }
+
+            result.push_str(&format!(" {line}\n"));
+        }
+
+        if old_end == lines.len() && !content.ends_with('\n') {
+            result.push_str("\\ No newline at end of file\n");
+        }
+
+        offset += added;
+    }
+
+    result
+}
+
+fn anchored(original: &[&str], documented: &[&str]) -> Vec<(usize, Vec<String>)> {
+    let mut result = vec![];
+    let mut i = 0;
+
+    for line in documented {
+        let found = (!line.trim().is_empty())
+            .then(|| {
+                original[i..]
+                    .iter()
+                    .take(ANCHOR_LOOKAHEAD)
+                    .position(|original| original.trim() == line.trim())
+            })
+            .flatten();
+
+        match found {
+            Some(offset) => i += offset + 1,
+            None => push_insertion(&mut result, i, line),
+        }
+    }
+
+    result
+}
+
+fn common_subsequence(original: &[&str], documented: &[&str]) -> Vec<(usize, Vec<String>)> {
+    let n = original.len();
+    let m = documented.len();
+    let same = |i: usize, j: usize| original[i].trim() == documented[j].trim();
+
+    let mut lengths = vec![0u32; (n + 1) * (m + 1)];
+    let index = |i: usize, j: usize| i * (m + 1) + j;
+
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lengths[index(i, j)] = if same(i, j) {
+                lengths[index(i + 1, j + 1)] + 1
+            } else {
+                lengths[index(i + 1, j)].max(lengths[index(i, j + 1)])
+            };
+        }
+    }
+
+    let mut result = vec![];
+    let (mut i, mut j) = (0, 0);
+
+    while j < m {
+        if i < n && same(i, j) {
+            i += 1;
+            j += 1;
+        } else if i < n && lengths[index(i + 1, j)] >= lengths[index(i, j + 1)] {
+            i += 1;
+        } else {
+            push_insertion(&mut result, i, documented[j]);
+            j += 1;
+        }
+    }
+
+    result
+}
+
+fn push_insertion(insertions: &mut Vec<(usize, Vec<String>)>, position: usize, line: &str) {
+    match insertions.last_mut() {
+        Some((last, lines)) if *last == position => lines.push(line.to_string()),
+        _ => insertions.push((position, vec![line.to_string()])),
+    }
+}
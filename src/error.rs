@@ -1,15 +1,78 @@
+use crate::mode::Mode;
 use std::{error, fmt};
 
 #[derive(Debug)]
 pub enum Error {
+    AttemptsExhausted { attempts: u8, last_error: String },
+    ChangedFilesMissing(usize),
+    ConfigFileInvalid { message: String, path: String },
+    ContextTooLarge { context_length: u32, tokens: usize },
+    CriteriaFileMissing,
+    GitCommandFailed,
+    ModeNotSupported(Mode),
     OllamaHostAddresMissing,
-    OllamaRequestProblem,
+    OpenAiBaseUrlMissing,
+    QuestionMissing,
+    RequestFailed { body: String, status: u16 },
+    StreamStalled,
+    UnknownBackend(String),
+    UnknownFormat(String),
+    UnknownMode(String),
+    UnknownSeverity,
 }
 
 impl error::Error for Error {}
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Error!")
+        match self {
+            Error::AttemptsExhausted {
+                attempts,
+                last_error,
+            } => write!(f, "request failed after {attempts} attempts: {last_error}"),
+            Error::ChangedFilesMissing(count) => write!(
+                f,
+                "none of the {count} files changed by the diff were found in the working tree, run ccw inside the repository of the diff"
+            ),
+            Error::ConfigFileInvalid { message, path } => {
+                write!(f, "invalid config file {path}: {message}")
+            }
+            Error::ContextTooLarge {
+                context_length,
+                tokens,
+            } => write!(
+                f,
+                "input needs about {tokens} tokens but the model context length is {context_length}, lower --chunk-size or use a model with a larger context"
+            ),
+            Error::CriteriaFileMissing => {
+                write!(f, "provide the acceptance criteria with --file")
+            }
+            Error::GitCommandFailed => write!(f, "git command failed"),
+            Error::ModeNotSupported(mode) => write!(f, "mode {mode} is not supported here"),
+            Error::OllamaHostAddresMissing => {
+                write!(f, "OLLAMA_HOST environment variable is not set")
+            }
+            Error::OpenAiBaseUrlMissing => {
+                write!(f, "OPENAI_BASE_URL environment variable is not set")
+            }
+            Error::QuestionMissing => write!(f, "provide the question with --question"),
+            Error::RequestFailed { body, status } => {
+                write!(f, "server responded with status {status}: {}", body.trim())
+            }
+            Error::StreamStalled => write!(f, "response stream stalled"),
+            Error::UnknownBackend(backend) => {
+                write!(f, "unknown backend {backend}, use ollama or openai")
+            }
+            Error::UnknownFormat(format) => {
+                write!(f, "unknown format {format}, use text, json, sarif or html")
+            }
+            Error::UnknownMode(mode) => write!(
+                f,
+                "unknown mode {mode}, use ask, checker, commit_review, commit_summary, convert_to_rust, criteria_verify, design_advice, doc_generate, explain, performance, task_generate, task_review or test_generate"
+            ),
+            Error::UnknownSeverity => {
+                write!(f, "unknown severity, use low, medium, high or critical")
+            }
+        }
     }
 }
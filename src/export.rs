@@ -0,0 +1,241 @@
+use crate::{
+    Result,
+    config::Config,
+    finding::Severity,
+    html,
+    output::{self, FileReport, Format, Report},
+};
+use serde::Serialize;
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+pub const INDEX_FILE: &str = "index";
+
+#[derive(Debug, Serialize)]
+pub struct Index {
+    pub files: Vec<IndexEntry>,
+    pub mode: String,
+    pub model: String,
+    pub signature: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IndexEntry {
+    pub file_name: Option<String>,
+    pub findings: Option<usize>,
+    pub report: String,
+    pub severity: Option<Severity>,
+    pub skipped: bool,
+    pub verdict: String,
+}
+
+pub fn extension(format: &Format) -> &'static str {
+    match format {
+        Format::Html => "html",
+        Format::Json => "json",
+        Format::Sarif => "sarif",
+        Format::Text => "md",
+    }
+}
+
+pub fn render(config: &Config, report: &Report) -> Result<String> {
+    match output::render(config, report)? {
+        Some(rendered) => Ok(format!("{rendered}\n")),
+        None if !output::prints_text(config) => Ok(report
+            .files
+            .iter()
+            .filter_map(|file_report| file_report.text.clone())
+            .collect()),
+        None => Ok(report
+            .files
+            .iter()
+            .map(|file_report| render_text(report, file_report))
+            .collect::<Vec<String>>()
+            .join("\n")),
+    }
+}
+
+pub fn render_text(report: &Report, file_report: &FileReport) -> String {
+    let title = file_report.file_name.as_deref().unwrap_or(&report.mode);
+    let text = match &file_report.text {
+        Some(text) => text.trim_end(),
+        None => "Skipped: the input is larger than --skip-larger.",
+    };
+
+    let mut result = format!("# {title}\n\n{text}\n\n");
+
+    let locations = output::locations_text(file_report);
+    if !locations.is_empty() {
+        result.push_str(&format!("{locations}\n"));
+    }
+
+    result.push_str(&format!("{}\n", report.signature));
+
+    result
+}
+
+pub fn report_path(mode: &str, file_report: &FileReport, format: &Format) -> PathBuf {
+    let extension = extension(format);
+
+    let Some(file_name) = &file_report.file_name else {
+        return PathBuf::from(format!("{mode}.{extension}"));
+    };
+
+    let path = Path::new(file_name)
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part),
+            _ => None,
+        })
+        .collect::<PathBuf>();
+
+    PathBuf::from(format!("{}.{extension}", path.display()))
+}
+
+pub fn verdict(file_report: &FileReport) -> String {
+    if file_report.skipped {
+        return "skipped".to_string();
+    }
+
+    let Some(findings) = &file_report.findings else {
+        return "done".to_string();
+    };
+
+    let severity = findings.iter().filter_map(|finding| finding.severity).max();
+
+    match (findings.len(), severity) {
+        (0, _) => "no findings".to_string(),
+        (1, Some(severity)) => format!("1 finding, {severity}"),
+        (1, None) => "1 finding".to_string(),
+        (count, Some(severity)) => format!("{count} findings, highest {severity}"),
+        (count, None) => format!("{count} findings"),
+    }
+}
+
+pub fn write(config: &Config, report: &Report) -> Result<()> {
+    if let Some(path) = &config.output {
+        write_file(Path::new(path), &render(config, report)?)?;
+
+        output::progress(config, &format!("Report written to {path}"));
+    }
+
+    if let Some(dir) = &config.output_dir {
+        let dir = Path::new(dir);
+        let index = write_dir(config, report, dir)?;
+
+        output::progress(config, &format!("Reports written to {}", index.display()));
+    }
+
+    Ok(())
+}
+
+fn render_index(config: &Config, report: &Report, index: &Index) -> Result<String> {
+    match config.format {
+        Format::Html => return Ok(format!("{}\n", html::render(&[report]))),
+        Format::Json | Format::Sarif => {
+            return Ok(format!("{}\n", serde_json::to_string_pretty(index)?));
+        }
+        Format::Text => {}
+    }
+
+    let mut result = format!(
+        "# {} report\n\nModel: {}\n\n| File | Verdict | Report |\n| --- | --- | --- |\n",
+        index.mode, index.model
+    );
+
+    for entry in &index.files {
+        let file_name = entry
+            .file_name
+            .as_deref()
+            .unwrap_or(&index.mode)
+            .replace('|', "\\|");
+
+        result.push_str(&format!(
+            "| {file_name} | {} | [{}]({}) |\n",
+            entry.verdict, entry.report, entry.report
+        ));
+    }
+
+    result.push_str(&format!("\n{}\n", index.signature));
+
+    Ok(result)
+}
+
+fn unique_path(path: PathBuf, used: &mut HashSet<PathBuf>) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = path.extension().unwrap_or_default().to_string_lossy();
+    let mut result = path.clone();
+    let mut i = 2;
+
+    while !used.insert(result.clone()) {
+        result = path.with_file_name(format!("{stem}-{i}.{extension}"));
+        i += 1;
+    }
+
+    result
+}
+
+fn write_dir(config: &Config, report: &Report, dir: &Path) -> Result<PathBuf> {
+    let extension = match config.format {
+        Format::Html => "html",
+        Format::Json | Format::Sarif => "json",
+        Format::Text => "md",
+    };
+    let index_path = PathBuf::from(format!("{INDEX_FILE}.{extension}"));
+    let mut used = HashSet::from([index_path.clone()]);
+
+    let mut index = Index {
+        files: vec![],
+        mode: report.mode.clone(),
+        model: report.model.clone(),
+        signature: report.signature.clone(),
+    };
+
+    for file_report in &report.files {
+        let path = unique_path(
+            report_path(&report.mode, file_report, &config.format),
+            &mut used,
+        );
+        let file_only = Report {
+            files: vec![file_report.clone()],
+            mode: report.mode.clone(),
+            model: report.model.clone(),
+            signature: report.signature.clone(),
+        };
+
+        write_file(&dir.join(&path), &render(config, &file_only)?)?;
+
+        index.files.push(IndexEntry {
+            file_name: file_report.file_name.clone(),
+            findings: file_report.findings.as_ref().map(Vec::len),
+            report: path.display().to_string(),
+            severity: file_report
+                .findings
+                .iter()
+                .flatten()
+                .filter_map(|finding| finding.severity)
+                .max(),
+            skipped: file_report.skipped,
+            verdict: verdict(file_report),
+        });
+    }
+
+    let index_path = dir.join(index_path);
+
+    write_file(&index_path, &render_index(config, report, &index)?)?;
+
+    Ok(index_path)
+}
+
+fn write_file(path: &Path, content: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    fs::write(path, content)?;
+
+    Ok(())
+}
@@ -1,19 +1,76 @@
-use crate::{Result, config::Config};
+use crate::{Result, config::Config, language::Registry, output};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
 use std::{fs::read_to_string, path::Path, sync::Arc};
-use walkdir::WalkDir;
+
+pub const CCW_IGNORE_FILE: &str = ".ccwignore";
+
+#[derive(Clone, Debug)]
+pub struct Source {
+    pub changes: Vec<(u32, u32)>,
+    pub code: String,
+    pub context: Option<String>,
+    pub language: String,
+    pub name: String,
+    pub start_line: u32,
+}
+
+impl Source {
+    pub fn new(name: &str, language: &str, code: &str) -> Self {
+        Self {
+            changes: vec![],
+            code: code.to_string(),
+            context: None,
+            language: language.to_string(),
+            name: name.to_string(),
+            start_line: 1,
+        }
+    }
+}
+
+pub fn first_line(config: &Config) -> u32 {
+    match (config.start_line, config.end_line) {
+        (Some(start_line), Some(_)) => start_line.max(1),
+        _ => 1,
+    }
+}
+
+pub fn glob_set(patterns: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+
+    for pattern in patterns {
+        builder.add(Glob::new(pattern)?);
+    }
+
+    Ok(builder.build()?)
+}
+
+pub fn is_included(
+    path: &Path,
+    dir: &Path,
+    include: &GlobSet,
+    exclude: &GlobSet,
+    include_all: bool,
+) -> bool {
+    let relative = path.strip_prefix(dir).unwrap_or(path);
+    let path = path.strip_prefix("./").unwrap_or(path);
+
+    if exclude.is_match(path) || exclude.is_match(relative) {
+        return false;
+    }
+
+    include_all || include.is_match(path) || include.is_match(relative)
+}
 
 pub fn read(config: &Arc<Config>, file: &str) -> Result<String> {
     let mut result = String::new();
     let file_content = read_to_string(file)?;
 
     if let (Some(start_line), Some(end_line)) = (config.start_line, config.end_line) {
-        let mut i = 1;
-        for line in file_content.lines() {
+        for (i, line) in (1..).zip(file_content.lines()) {
             if i >= start_line && i <= end_line {
                 result.push_str(&format!("{line}\n"));
             }
-
-            i += 1;
         }
     } else {
         result = file_content;
@@ -22,42 +79,88 @@ pub fn read(config: &Arc<Config>, file: &str) -> Result<String> {
     Ok(result)
 }
 
-pub fn read_files(config: &Arc<Config>) -> Result<Vec<(String, String)>> {
+pub fn number_lines(code: &str, first_line: u32) -> String {
+    let last_line = first_line as usize + code.lines().count();
+    let width = last_line.to_string().len();
+
+    let mut result = String::with_capacity(code.len() + code.lines().count() * (width + 3));
+
+    for (i, line) in (first_line..).zip(code.lines()) {
+        result.push_str(&format!("{i:>width$} | {line}\n"));
+    }
+
+    result
+}
+
+pub fn read_files(config: &Arc<Config>) -> Result<Vec<Source>> {
     let mut result = vec![];
 
-    let allowed_extensions = vec![
-        "c", "h", // C
-        "cpp", "hpp", "cc", "hh", "cxx", "hxx", // C++
-        "cs",  // C#
-        "go",  // Go
-        "java", "js", // Java ;)
-        "py", // Python
-        "rs", // Rust
-        "ts", // TypeScript
-    ];
+    let registry = Registry::new(&config.languages);
 
     if let Some(dir) = &config.dir {
-        for entry in WalkDir::new(dir) {
+        let include = glob_set(&config.include)?;
+        let exclude = glob_set(&config.exclude)?;
+
+        let walk = WalkBuilder::new(dir)
+            .add_custom_ignore_filename(CCW_IGNORE_FILE)
+            .require_git(false)
+            .sort_by_file_name(|a, b| a.cmp(b))
+            .build();
+
+        for entry in walk {
             let entry = entry?;
             let path = entry.path();
 
-            if let Some(ext) = path.extension().and_then(|s| s.to_str())
-                && allowed_extensions.contains(&ext)
+            if !entry
+                .file_type()
+                .is_some_and(|file_type| file_type.is_file())
+            {
+                continue;
+            }
+
+            if is_included(
+                path,
+                Path::new(dir),
+                &include,
+                &exclude,
+                config.include.is_empty(),
+            ) && let Some(language) = registry.detect(path)
             {
-                let file = format!("{}", path.display());
-                let content = read(config, &file)?;
-                result.push((file, content));
+                let name = format!("{}", path.display());
+                let code = read(config, &name)?;
+                result.push(Source {
+                    changes: vec![],
+                    code,
+                    context: None,
+                    language: language.to_string(),
+                    name,
+                    start_line: first_line(config),
+                });
             }
         }
     }
 
-    if let Some(file) = &config.file
-        && let Some(ext) = Path::new(file).extension().and_then(|s| s.to_str())
-        && allowed_extensions.contains(&ext)
-    {
-        let content = read(config, file)?;
+    if let Some(file) = &config.file {
+        match registry.detect(Path::new(file)) {
+            Some(language) => {
+                let code = read(config, file)?;
 
-        result.push((file.clone(), content));
+                result.push(Source {
+                    changes: vec![],
+                    code,
+                    context: None,
+                    language: language.to_string(),
+                    name: file.clone(),
+                    start_line: first_line(config),
+                });
+            }
+            None => output::warn(
+                config,
+                &format!(
+                    "Skipping {file}: unknown language. Add it to the languages list in the configuration file."
+                ),
+            ),
+        }
     }
 
     Ok(result)
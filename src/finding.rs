@@ -0,0 +1,288 @@
+use crate::{error::Error, mode::Mode};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::{fmt, str::FromStr};
+
+pub const LINE_NUMBERS_PROMPT: &str = "Each line of the code starts with its line number followed by \"|\". The numbers are not part of the code. When a finding refers to specific code, cite the exact line range as \"Lines: N-M\" (or \"Line: N\") using these numbers.";
+pub const JSON_PROMPT: &str = "Respond ONLY with a JSON object that matches the provided schema. Put every finding in the \"findings\" array using the fields summary, description, snippet, recommendation and severity (critical, high, medium or low). When the finding refers to specific lines, set start_line and end_line. If there are no findings, return an empty \"findings\" array.";
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Finding {
+    pub summary: String,
+    pub description: String,
+    #[serde(default)]
+    pub snippet: Option<String>,
+    pub recommendation: String,
+    #[serde(default)]
+    pub severity: Option<Severity>,
+    #[serde(default)]
+    pub start_line: Option<u32>,
+    #[serde(default)]
+    pub end_line: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Findings {
+    findings: Vec<Finding>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    fn heading(line: &str) -> Option<Self> {
+        let lowercase = line.to_lowercase();
+        let rest = lowercase.strip_prefix("severity")?;
+
+        if !rest.starts_with(|c: char| c == ':' || c == '*' || c.is_whitespace()) {
+            return None;
+        }
+
+        rest.split(|c: char| !c.is_alphabetic())
+            .find_map(|word| match word {
+                "moderate" => Some(Severity::Medium),
+                word => word.parse().ok(),
+            })
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self {
+            Severity::Critical => "critical",
+            Severity::High => "high",
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+        };
+
+        write!(f, "{severity}")
+    }
+}
+
+impl FromStr for Severity {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let lowercase = s.to_string().to_lowercase();
+        let s = lowercase.as_str();
+        match s {
+            "critical" => Ok(Severity::Critical),
+            "high" => Ok(Severity::High),
+            "low" => Ok(Severity::Low),
+            "medium" => Ok(Severity::Medium),
+            _ => Err(Error::UnknownSeverity),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Section {
+    Description,
+    Recommendation,
+    Snippet,
+    Summary,
+}
+
+pub fn has_findings(mode: &Mode) -> bool {
+    matches!(mode, Mode::Checker | Mode::CommitReview | Mode::Performance)
+}
+
+pub fn schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "findings": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "summary": { "type": "string" },
+                        "description": { "type": "string" },
+                        "snippet": { "type": "string" },
+                        "recommendation": { "type": "string" },
+                        "severity": {
+                            "type": "string",
+                            "enum": ["critical", "high", "medium", "low"]
+                        },
+                        "start_line": { "type": "integer" },
+                        "end_line": { "type": "integer" }
+                    },
+                    "required": ["summary", "description", "recommendation"]
+                }
+            }
+        },
+        "required": ["findings"]
+    })
+}
+
+pub fn parse(text: &str) -> Vec<Finding> {
+    if let Ok(findings) = serde_json::from_str::<Findings>(text.trim()) {
+        return findings.findings;
+    }
+
+    parse_template(text)
+}
+
+pub fn parse_template(text: &str) -> Vec<Finding> {
+    let mut result = vec![];
+
+    for block in split_blocks(text) {
+        let mut finding = Finding::default();
+        let mut snippet = String::new();
+        let mut section = None;
+
+        for line in block.lines() {
+            let heading = line
+                .trim()
+                .trim_matches(|c: char| c == '#' || c == '*' || c.is_whitespace());
+
+            if let Some(severity) = Severity::heading(heading) {
+                finding.severity = Some(severity);
+
+                continue;
+            }
+
+            if let Some((new_section, rest)) = section_heading(heading) {
+                section = Some(new_section);
+
+                if !rest.is_empty() {
+                    push_line(&mut finding, &mut snippet, new_section, rest);
+                }
+
+                continue;
+            }
+
+            if let Some(section) = section {
+                push_line(&mut finding, &mut snippet, section, line);
+            }
+        }
+
+        finding.summary = finding.summary.trim().to_string();
+        finding.description = finding.description.trim().to_string();
+        finding.recommendation = finding.recommendation.trim().to_string();
+
+        let snippet = snippet.trim_matches('\n');
+        if !snippet.trim().is_empty() {
+            finding.snippet = Some(snippet.to_string());
+        }
+
+        if let Some((start_line, end_line)) = parse_lines(&block) {
+            finding.start_line = Some(start_line);
+            finding.end_line = Some(end_line);
+        }
+
+        if !finding.summary.is_empty() {
+            result.push(finding);
+        }
+    }
+
+    result
+}
+
+pub fn parse_lines(text: &str) -> Option<(u32, u32)> {
+    let lowercase = text.to_lowercase();
+    let words = lowercase
+        .split(|c: char| c.is_whitespace() || c == ':' || c == ',' || c == '(' || c == ')')
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<&str>>();
+
+    for (i, word) in words.iter().enumerate() {
+        if *word != "line" && *word != "lines" {
+            continue;
+        }
+
+        let Some(range) = words.get(i + 1) else {
+            continue;
+        };
+        let range = range.trim_end_matches('.');
+
+        let (start, end) = match range.split_once(['-', '\u{2013}']) {
+            Some((start, end)) => (start, end),
+            None => (range, range),
+        };
+
+        if let (Ok(start_line), Ok(end_line)) = (start.parse::<u32>(), end.parse::<u32>())
+            && start_line > 0
+            && start_line <= end_line
+        {
+            return Some((start_line, end_line));
+        }
+    }
+
+    None
+}
+
+fn push_line(finding: &mut Finding, snippet: &mut String, section: Section, line: &str) {
+    let target = match section {
+        Section::Description => &mut finding.description,
+        Section::Recommendation => &mut finding.recommendation,
+        Section::Snippet => snippet,
+        Section::Summary => &mut finding.summary,
+    };
+
+    target.push_str(line);
+    target.push('\n');
+}
+
+fn section_heading(line: &str) -> Option<(Section, &str)> {
+    let headings = [
+        ("problem summary", Section::Summary),
+        ("problem detailed description", Section::Description),
+        ("relevant code snippet (optional)", Section::Snippet),
+        ("relevant code snippet", Section::Snippet),
+        ("optional code example", Section::Snippet),
+        ("recommendation to fix", Section::Recommendation),
+        ("recommended fix", Section::Recommendation),
+        ("recommendation", Section::Recommendation),
+    ];
+
+    let lowercase = line.to_lowercase();
+
+    for (heading, section) in headings {
+        if lowercase.starts_with(heading) {
+            let rest = line
+                .get(heading.len()..)
+                .unwrap_or_default()
+                .trim_start_matches(|c: char| c == ':' || c == '*' || c.is_whitespace());
+
+            return Some((section, rest));
+        }
+    }
+
+    None
+}
+
+fn split_blocks(text: &str) -> Vec<String> {
+    let mut result = vec![];
+    let mut block = String::new();
+
+    for line in text.lines() {
+        let trimmed = line.trim();
+
+        if trimmed.len() >= 3 && trimmed.chars().all(|c| c == '=') {
+            if !block.trim().is_empty() {
+                result.push(block.clone());
+            }
+            block.clear();
+
+            continue;
+        }
+
+        block.push_str(line);
+        block.push('\n');
+    }
+
+    if !block.trim().is_empty() {
+        result.push(block);
+    }
+
+    result
+}
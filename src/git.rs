@@ -0,0 +1,78 @@
+use crate::{Result, config::Config, error::Error, output};
+use std::path::PathBuf;
+use tokio::process::Command;
+
+pub const COMMIT_METADATA_FORMAT: &str = "Commit: %H%nAuthor: %an <%ae>%nDate: %ad%n%nMessage:%n%B";
+pub const DIFF_OPTIONS: &[&str] = &[
+    "--no-ext-diff",
+    "--no-color",
+    "--src-prefix=a/",
+    "--dst-prefix=b/",
+];
+
+pub async fn git(config: &Config, args: &[&str]) -> Result<String> {
+    let output = Command::new("git").args(args).output().await?;
+
+    if !output.status.success() {
+        output::warn(config, String::from_utf8_lossy(&output.stderr).trim_end());
+
+        return Err(Box::new(Error::GitCommandFailed));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+pub async fn read_diff(config: &Config) -> Result<Option<String>> {
+    if config.staged {
+        let diff = git(config, &diff_args("diff", &["--staged"])).await?;
+
+        return Ok(Some(diff));
+    }
+
+    if let Some(commit) = &config.commit {
+        let format = format!("--format={COMMIT_METADATA_FORMAT}");
+        let metadata = git(
+            config,
+            &diff_args("show", &["--no-patch", &format, "--end-of-options", commit]),
+        )
+        .await?;
+        let diff = git(
+            config,
+            &diff_args("show", &["--format=", "--end-of-options", commit]),
+        )
+        .await?;
+
+        let result = format!("Commit metadata:\n{}\n\nDiff:\n{diff}", metadata.trim_end());
+
+        return Ok(Some(result));
+    }
+
+    if let Some(range) = &config.range {
+        let diff = git(config, &diff_args("diff", &["--end-of-options", range])).await?;
+
+        return Ok(Some(diff));
+    }
+
+    Ok(None)
+}
+
+pub async fn toplevel() -> Option<PathBuf> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--show-toplevel"])
+        .output()
+        .await
+        .ok()?;
+
+    output
+        .status
+        .success()
+        .then(|| PathBuf::from(String::from_utf8_lossy(&output.stdout).trim_end()))
+}
+
+fn diff_args<'a>(command: &'a str, args: &[&'a str]) -> Vec<&'a str> {
+    let mut result = vec![command];
+    result.extend(DIFF_OPTIONS);
+    result.extend(args);
+
+    result
+}
@@ -0,0 +1,367 @@
+use crate::{
+    export,
+    file::Source,
+    finding::{Finding, Severity},
+    output::{FileReport, Report},
+};
+use std::collections::BTreeMap;
+
+pub const EXCERPT_CONTEXT_LINES: u32 = 2;
+
+const SCRIPT: &str = r##"
+function applyFilters() {
+  const mode = document.getElementById("mode").value;
+  const severities = Array.from(document.querySelectorAll("input[name=severity]:checked")).map((input) => input.value);
+  document.querySelectorAll("[data-mode]").forEach((element) => {
+    element.hidden = mode !== "" && element.dataset.mode !== mode;
+  });
+  document.querySelectorAll(".finding").forEach((element) => {
+    element.hidden = !severities.includes(element.dataset.severity);
+  });
+}
+document.querySelectorAll("#filters select, #filters input").forEach((input) => input.addEventListener("change", applyFilters));
+"##;
+
+const STYLE: &str = r##"
+body { display: flex; font-family: system-ui, sans-serif; margin: 0; color: #1f2328; }
+nav { position: sticky; top: 0; height: 100vh; overflow: auto; width: 20rem; flex-shrink: 0; padding: 1rem; box-sizing: border-box; background: #f6f8fa; border-right: 1px solid #d0d7de; font-size: 0.9rem; }
+nav ul { list-style: none; padding-left: 1rem; margin: 0.2rem 0; }
+nav > ul { padding-left: 0; }
+nav a { color: inherit; text-decoration: none; }
+nav a:hover { text-decoration: underline; }
+main { flex-grow: 1; padding: 1rem 2rem; min-width: 0; }
+table.metadata td { padding: 0.1rem 1rem 0.1rem 0; }
+#filters { margin: 1rem 0; padding: 0.5rem 0; border-top: 1px solid #d0d7de; border-bottom: 1px solid #d0d7de; }
+#filters label { margin-right: 1rem; }
+section.file { border-top: 1px solid #d0d7de; padding-top: 0.5rem; }
+.finding { border: 1px solid #d0d7de; border-radius: 6px; padding: 0 1rem; margin: 1rem 0; }
+.verdict, .location { color: #59636e; font-size: 0.9rem; }
+.severity { border-radius: 4px; padding: 0.1rem 0.4rem; font-size: 0.8rem; color: #fff; }
+.severity.critical { background: #82071e; }
+.severity.high { background: #cf222e; }
+.severity.medium { background: #bc4c00; }
+.severity.low { background: #0969da; }
+pre { background: #f6f8fa; padding: 0.5rem; overflow: auto; white-space: pre-wrap; }
+pre.code { white-space: pre; }
+pre.code .number { color: #8c959f; user-select: none; }
+pre.code .marked { background: #fff8c5; display: inline-block; width: 100%; }
+"##;
+
+#[derive(Debug, Default)]
+struct Tree {
+    children: BTreeMap<String, Tree>,
+    files: Vec<TreeFile>,
+}
+
+#[derive(Debug)]
+struct TreeFile {
+    id: String,
+    mode: String,
+    name: String,
+    verdict: String,
+}
+
+impl Tree {
+    fn insert(&mut self, path: &str, file: TreeFile) {
+        let mut node = self;
+        let mut parts = path
+            .split('/')
+            .filter(|part| !part.is_empty() && *part != ".");
+        let mut name = parts.next().unwrap_or(path);
+
+        for part in parts {
+            node = node.children.entry(name.to_string()).or_default();
+            name = part;
+        }
+
+        node.files.push(TreeFile {
+            name: name.to_string(),
+            ..file
+        });
+    }
+
+    fn render(&self) -> String {
+        let mut result = String::from("<ul>\n");
+
+        for (name, child) in &self.children {
+            result.push_str(&format!(
+                "<li><details open><summary>{}</summary>\n{}</details></li>\n",
+                escape(name),
+                child.render()
+            ));
+        }
+
+        for file in &self.files {
+            result.push_str(&format!(
+                "<li data-mode=\"{}\"><a href=\"#{}\">{}</a> <span class=\"verdict\">{}</span></li>\n",
+                escape(&file.mode),
+                file.id,
+                escape(&file.name),
+                escape(&file.verdict)
+            ));
+        }
+
+        result.push_str("</ul>\n");
+
+        result
+    }
+}
+
+pub fn render(reports: &[&Report]) -> String {
+    let mut tree = Tree::default();
+    let mut sections = String::new();
+
+    for (i, (report, file_report)) in reports
+        .iter()
+        .flat_map(|report| {
+            report
+                .files
+                .iter()
+                .map(move |file_report| (*report, file_report))
+        })
+        .enumerate()
+    {
+        let id = format!("file-{i}");
+        let name = file_report.file_name.as_deref().unwrap_or(&report.mode);
+
+        tree.insert(
+            name,
+            TreeFile {
+                id: id.clone(),
+                mode: report.mode.clone(),
+                name: name.to_string(),
+                verdict: export::verdict(file_report),
+            },
+        );
+        sections.push_str(&render_file(&id, report, file_report));
+    }
+
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n<nav>\n<strong>Files</strong>\n{tree}</nav>\n<main>\n<h1>{title}</h1>\n{metadata}{filters}{sections}</main>\n<script>{SCRIPT}</script>\n</body>\n</html>",
+        title = escape(&title(reports)),
+        tree = tree.render(),
+        metadata = render_metadata(reports),
+        filters = render_filters(reports),
+    )
+}
+
+pub fn excerpt(source: &Source, start_line: u32, end_line: u32) -> Option<String> {
+    let lines = source.code.lines().collect::<Vec<&str>>();
+    let last_line = source.start_line + u32::try_from(lines.len()).ok()? - 1;
+
+    if start_line < source.start_line || start_line > last_line {
+        return None;
+    }
+
+    let first = start_line
+        .saturating_sub(EXCERPT_CONTEXT_LINES)
+        .max(source.start_line);
+    let last = (end_line + EXCERPT_CONTEXT_LINES).min(last_line);
+    let width = last.to_string().len();
+
+    let mut result = String::from("<pre class=\"code\">");
+
+    for number in first..=last {
+        let line = lines[usize::try_from(number - source.start_line).ok()?];
+        let line = format!(
+            "<span class=\"number\">{number:>width$} | </span>{}",
+            escape(line)
+        );
+
+        if (start_line..=end_line).contains(&number) {
+            result.push_str(&format!("<span class=\"marked\">{line}</span>\n"));
+        } else {
+            result.push_str(&format!("{line}\n"));
+        }
+    }
+
+    result.push_str("</pre>\n");
+
+    Some(result)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+fn render_file(id: &str, report: &Report, file_report: &FileReport) -> String {
+    let name = file_report.file_name.as_deref().unwrap_or(&report.mode);
+    let seconds = file_report
+        .results
+        .iter()
+        .map(|result| result.seconds())
+        .sum::<i64>();
+
+    let mut result = format!(
+        "<section class=\"file\" id=\"{id}\" data-mode=\"{}\">\n<h2>{}</h2>\n<p class=\"verdict\">{} &middot; {} &middot; {seconds} seconds</p>\n",
+        escape(&report.mode),
+        escape(name),
+        escape(&export::verdict(file_report)),
+        escape(&report.mode)
+    );
+
+    match (&file_report.findings, &file_report.text) {
+        (Some(findings), _) => {
+            for finding in findings {
+                result.push_str(&render_finding(file_report.source.as_ref(), finding));
+            }
+        }
+        (None, Some(text)) => result.push_str(&format!("<pre>{}</pre>\n", escape(text))),
+        (None, None) => {}
+    }
+
+    result.push_str("</section>\n");
+
+    result
+}
+
+fn render_filters(reports: &[&Report]) -> String {
+    let mut modes = reports
+        .iter()
+        .map(|report| report.mode.as_str())
+        .collect::<Vec<&str>>();
+    modes.sort_unstable();
+    modes.dedup();
+
+    let mut result = String::from(
+        "<div id=\"filters\">\n<label>Mode <select id=\"mode\"><option value=\"\">all</option>",
+    );
+
+    for mode in modes {
+        result.push_str(&format!("<option>{}</option>", escape(mode)));
+    }
+
+    result.push_str("</select></label>\nSeverity ");
+
+    for severity in [
+        Severity::Critical,
+        Severity::High,
+        Severity::Medium,
+        Severity::Low,
+    ] {
+        result.push_str(&format!(
+            "<label><input type=\"checkbox\" name=\"severity\" value=\"{severity}\" checked> {severity}</label>"
+        ));
+    }
+
+    result.push_str("\n</div>\n");
+
+    result
+}
+
+fn render_finding(source: Option<&Source>, finding: &Finding) -> String {
+    let severity = finding.severity.unwrap_or(Severity::Medium);
+
+    let mut result = format!(
+        "<article class=\"finding\" data-severity=\"{severity}\">\n<h3><span class=\"severity {severity}\">{severity}</span> {}</h3>\n",
+        escape(&finding.summary)
+    );
+
+    if let Some(location) = &finding.location {
+        result.push_str(&format!("<p class=\"location\">{}</p>\n", escape(location)));
+    }
+
+    result.push_str(&format!(
+        "<h4>Problem detailed description</h4>\n<p>{}</p>\n",
+        escape(&finding.description)
+    ));
+
+    let code = match (source, finding.start_line) {
+        (Some(source), Some(start_line)) => excerpt(
+            source,
+            start_line,
+            finding.end_line.unwrap_or(start_line).max(start_line),
+        ),
+        _ => None,
+    };
+    let code = code.or_else(|| {
+        finding
+            .snippet
+            .as_ref()
+            .map(|snippet| format!("<pre class=\"code\">{}</pre>\n", escape(snippet)))
+    });
+
+    if let Some(code) = code {
+        result.push_str(&format!("<h4>Relevant code snippet</h4>\n{code}"));
+    }
+
+    result.push_str(&format!(
+        "<h4>Recommendation to fix</h4>\n<p>{}</p>\n</article>\n",
+        escape(&finding.recommendation)
+    ));
+
+    result
+}
+
+fn render_metadata(reports: &[&Report]) -> String {
+    let results = reports
+        .iter()
+        .flat_map(|report| &report.files)
+        .flat_map(|file_report| &file_report.results)
+        .collect::<Vec<_>>();
+    let started_at = results.iter().map(|result| result.started_at).min();
+    let finished_at = results.iter().map(|result| result.finished_at).max();
+    let files = reports
+        .iter()
+        .map(|report| report.files.len())
+        .sum::<usize>();
+    let findings = reports
+        .iter()
+        .flat_map(|report| &report.files)
+        .map(|file_report| file_report.findings.as_ref().map_or(0, Vec::len))
+        .sum::<usize>();
+
+    let mut models = reports
+        .iter()
+        .map(|report| report.model.as_str())
+        .collect::<Vec<&str>>();
+    models.dedup();
+    let mut signatures = reports
+        .iter()
+        .map(|report| report.signature.as_str())
+        .collect::<Vec<&str>>();
+    signatures.dedup();
+
+    let mut rows = vec![
+        ("Model", models.join(", ")),
+        ("Files", files.to_string()),
+        ("Findings", findings.to_string()),
+    ];
+
+    if let (Some(started_at), Some(finished_at)) = (started_at, finished_at) {
+        rows.push(("Started", started_at.to_rfc3339()));
+        rows.push((
+            "Duration",
+            format!("{} seconds", (finished_at - started_at).num_seconds()),
+        ));
+    }
+
+    rows.push(("Signature", signatures.join(", ")));
+
+    let mut result = String::from("<table class=\"metadata\">\n");
+
+    for (name, value) in rows {
+        result.push_str(&format!(
+            "<tr><td>{name}</td><td>{}</td></tr>\n",
+            escape(&value)
+        ));
+    }
+
+    result.push_str("</table>\n");
+
+    result
+}
+
+fn title(reports: &[&Report]) -> String {
+    let mut modes = reports
+        .iter()
+        .map(|report| report.mode.as_str())
+        .collect::<Vec<&str>>();
+    modes.dedup();
+
+    format!("CCW {} report", modes.join(", "))
+}
@@ -0,0 +1,128 @@
+use serde::Deserialize;
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Read},
+    path::Path,
+};
+
+pub const SHEBANG_MAX_LENGTH: u64 = 256;
+
+pub const LANGUAGES: &[(&str, &[&str], &[&str])] = &[
+    ("C", &["c", "h"], &["tcc"]),
+    ("C#", &["cs", "csx"], &[]),
+    (
+        "C++",
+        &["cpp", "hpp", "cc", "hh", "cxx", "hxx", "c++", "h++"],
+        &[],
+    ),
+    ("Dart", &["dart"], &["dart"]),
+    ("Elixir", &["ex", "exs"], &["elixir"]),
+    ("Erlang", &["erl", "hrl"], &["escript"]),
+    ("Go", &["go"], &[]),
+    ("Haskell", &["hs"], &["runhaskell"]),
+    ("Java", &["java"], &[]),
+    (
+        "JavaScript",
+        &["js", "mjs", "cjs", "jsx"],
+        &["node", "deno"],
+    ),
+    ("Kotlin", &["kt", "kts"], &["kotlin"]),
+    ("Lua", &["lua"], &["lua", "luajit"]),
+    ("Objective-C", &["m", "mm"], &[]),
+    ("Perl", &["pl", "pm"], &["perl"]),
+    ("PHP", &["php"], &["php"]),
+    (
+        "Python",
+        &["py", "pyw", "pyi"],
+        &["python", "python2", "python3"],
+    ),
+    ("Ruby", &["rb", "rake"], &["ruby"]),
+    ("Rust", &["rs"], &[]),
+    ("Scala", &["scala", "sc"], &["scala"]),
+    (
+        "Shell",
+        &["sh", "bash", "zsh", "ksh"],
+        &["sh", "bash", "zsh", "ksh", "dash"],
+    ),
+    ("SQL", &["sql"], &[]),
+    ("Swift", &["swift"], &["swift"]),
+    (
+        "TypeScript",
+        &["ts", "tsx", "mts", "cts"],
+        &["ts-node", "tsx"],
+    ),
+    ("Zig", &["zig"], &[]),
+];
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Language {
+    pub name: String,
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    #[serde(default)]
+    pub shebangs: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Registry {
+    languages: Vec<Language>,
+}
+
+impl Registry {
+    pub fn new(additions: &[Language]) -> Self {
+        let mut languages = additions.to_vec();
+
+        for (name, extensions, shebangs) in LANGUAGES {
+            languages.push(Language {
+                name: name.to_string(),
+                extensions: extensions.iter().map(|s| s.to_string()).collect(),
+                shebangs: shebangs.iter().map(|s| s.to_string()).collect(),
+            });
+        }
+
+        Self { languages }
+    }
+
+    pub fn detect(&self, path: &Path) -> Option<&str> {
+        if let Some(ext) = path.extension().and_then(|s| s.to_str()) {
+            let ext = ext.to_lowercase();
+
+            return self
+                .languages
+                .iter()
+                .find(|language| language.extensions.contains(&ext))
+                .map(|language| language.name.as_str());
+        }
+
+        let interpreter = shebang(path)?;
+        let unversioned = interpreter
+            .trim_end_matches(|c: char| c.is_ascii_digit() || c == '.')
+            .to_string();
+
+        self.languages
+            .iter()
+            .find(|language| {
+                language.shebangs.contains(&interpreter) || language.shebangs.contains(&unversioned)
+            })
+            .map(|language| language.name.as_str())
+    }
+}
+
+fn shebang(path: &Path) -> Option<String> {
+    let file = File::open(path).ok()?;
+    let mut first_line = String::new();
+    BufReader::new(file.take(SHEBANG_MAX_LENGTH))
+        .read_line(&mut first_line)
+        .ok()?;
+
+    let command = first_line.strip_prefix("#!")?.trim();
+    let mut words = command.split_whitespace();
+    let mut program = words.next()?.rsplit('/').next()?;
+
+    if program == "env" {
+        program = words.find(|word| !word.starts_with('-'))?;
+    }
+
+    Some(program.to_string())
+}
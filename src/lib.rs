@@ -0,0 +1,52 @@
+#![forbid(unsafe_code)]
+
+use std::error::Error;
+
+pub mod app;
+pub mod ask;
+pub mod backend;
+pub mod cache;
+pub mod chat;
+pub mod checker;
+mod chunk;
+pub mod client;
+pub mod commit_review;
+pub mod commit_summary;
+pub mod config;
+mod config_file;
+pub mod convert_to_rust;
+pub mod criteria_verify;
+pub mod design_advice;
+pub mod diff;
+pub mod doc_generate;
+pub mod error;
+pub mod explain;
+pub mod export;
+pub mod file;
+pub mod finding;
+mod git;
+pub mod html;
+pub mod language;
+pub mod mode;
+mod ollama;
+mod openai;
+pub mod output;
+pub mod performance;
+pub mod prompt;
+pub mod sarif;
+pub mod task_generate;
+pub mod task_review;
+pub mod test_generate;
+mod token;
+
+pub use crate::{
+    backend::Backend,
+    client::Client,
+    config::Config,
+    file::Source,
+    finding::{Finding, Severity},
+    mode::Mode,
+    output::{FileReport, Format},
+};
+
+pub type Result<T> = std::result::Result<T, Box<dyn Error + Send + Sync>>;
@@ -1,33 +1,18 @@
 #![forbid(unsafe_code)]
 
-use std::error::Error;
-
-mod app;
-mod ask;
-mod checker;
-mod commit_review;
-mod commit_summary;
-mod config;
-mod convert_to_rust;
-mod criteria_verify;
-mod design_advice;
-mod error;
-mod explain;
-mod file;
-mod ollama;
-mod performance;
-mod task_generate;
-mod task_review;
-
-type Result<T> = std::result::Result<T, Box<dyn Error + Send + Sync>>;
+use ccw::app;
+use std::process::ExitCode;
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> ExitCode {
     let result = app::run();
 
-    if let Err(e) = result.await {
-        eprint!("Error: {e:?}");
-    }
+    match result.await {
+        Err(e) => {
+            eprintln!("Error: {e}");
 
-    Ok(())
+            ExitCode::FAILURE
+        }
+        Ok(exit_code) => exit_code,
+    }
 }
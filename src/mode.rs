@@ -0,0 +1,90 @@
+use crate::error::Error;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::{fmt, str::FromStr};
+
+#[derive(Clone, Debug, Default)]
+pub enum Mode {
+    Ask,
+    #[default]
+    Checker,
+    CommitReview,
+    CommitSummary,
+    ConvertToRust,
+    CriteriaVerify,
+    DesignAdvice,
+    DocGenerate,
+    Explain,
+    Performance,
+    TaskGenerate,
+    TaskReview,
+    TestGenerate,
+}
+
+impl FromStr for Mode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let lowercase = s.to_string().to_lowercase();
+        let s = lowercase.as_str();
+        match s {
+            "ask" => Ok(Mode::Ask),
+            "checker" => Ok(Mode::Checker),
+            "commit_review" => Ok(Mode::CommitReview),
+            "commit_summary" => Ok(Mode::CommitSummary),
+            "convert_to_rust" => Ok(Mode::ConvertToRust),
+            "criteria_verify" => Ok(Mode::CriteriaVerify),
+            "design_advice" => Ok(Mode::DesignAdvice),
+            "doc_generate" => Ok(Mode::DocGenerate),
+            "explain" => Ok(Mode::Explain),
+            "performance" => Ok(Mode::Performance),
+            "task_generate" => Ok(Mode::TaskGenerate),
+            "task_review" => Ok(Mode::TaskReview),
+            "test_generate" => Ok(Mode::TestGenerate),
+            _ => Err(Error::UnknownMode(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mode = match self {
+            Mode::Ask => "ask",
+            Mode::Checker => "checker",
+            Mode::CommitReview => "commit_review",
+            Mode::CommitSummary => "commit_summary",
+            Mode::ConvertToRust => "convert_to_rust",
+            Mode::CriteriaVerify => "criteria_verify",
+            Mode::DesignAdvice => "design_advice",
+            Mode::DocGenerate => "doc_generate",
+            Mode::Explain => "explain",
+            Mode::Performance => "performance",
+            Mode::TaskGenerate => "task_generate",
+            Mode::TaskReview => "task_review",
+            Mode::TestGenerate => "test_generate",
+        };
+
+        write!(f, "{mode}")
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ModeResult {
+    pub cached: bool,
+    pub completion_tokens: Option<u32>,
+    pub estimated_prompt_tokens: usize,
+    pub finished_at: DateTime<Utc>,
+    pub model: String,
+    pub num_ctx: u32,
+    pub prompt_tokens: Option<u32>,
+    pub skipped: bool,
+    pub started_at: DateTime<Utc>,
+    #[serde(skip)]
+    pub text: Option<String>,
+}
+
+impl ModeResult {
+    pub fn seconds(&self) -> i64 {
+        (self.finished_at - self.started_at).num_seconds()
+    }
+}
@@ -1,22 +1,16 @@
-use crate::{Result, app, app::Mode, config::Config, error::Error};
-use async_recursion::async_recursion;
-use chrono::{DateTime, Utc};
-use reqwest::StatusCode;
+use crate::{
+    Result,
+    backend::{self, Completion, DEFAULT_CODE_NUM_CTX, Message},
+    config::Config,
+};
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
-use tokio::time::Duration;
-
-pub const DEFAULT_CODE_MODEL: &str = "qwen3-coder:30b";
-pub const DEFAULT_CODE_NUM_CTX: u32 = 16384;
-
-#[derive(Clone, Debug, Deserialize, Serialize)]
-pub struct Message {
-    pub content: String,
-    pub role: String,
-}
+use serde_json::Value;
+use std::{collections::HashMap, sync::Arc};
 
 #[derive(Debug, Serialize)]
-pub struct OllamaRequest {
+pub struct OllamaRequest<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<&'a Value>,
     pub keep_alive: u16,
     pub messages: Vec<Message>,
     pub model: String,
@@ -26,7 +20,24 @@ pub struct OllamaRequest {
 
 #[derive(Debug, Deserialize)]
 pub struct OllamaResponse {
+    #[serde(default)]
+    done: bool,
+    #[serde(default)]
+    eval_count: Option<u32>,
     message: Message,
+    #[serde(default)]
+    prompt_eval_count: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OllamaShowRequest {
+    pub model: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OllamaShowResponse {
+    #[serde(default)]
+    model_info: HashMap<String, Value>,
 }
 
 #[derive(Debug, Serialize)]
@@ -35,123 +46,92 @@ pub struct Options {
     pub temperature: f32,
 }
 
-#[async_recursion]
+pub async fn context_length(config: &Config) -> Result<Option<u32>> {
+    let ollama_show_request = OllamaShowRequest {
+        model: backend::model(config),
+    };
+
+    let url = format!("{}/api/show", config.host);
+
+    let response = backend::client(config)?
+        .post(url)
+        .json(&ollama_show_request)
+        .send()
+        .await?;
+    let response = backend::check_status(response).await?;
+
+    let ollama_show_response: OllamaShowResponse = response.json().await?;
+
+    let context_length = ollama_show_response
+        .model_info
+        .iter()
+        .find(|(key, _)| key.ends_with(".context_length"))
+        .and_then(|(_, value)| value.as_u64())
+        .and_then(|value| u32::try_from(value).ok());
+
+    Ok(context_length)
+}
+
 pub async fn request(
     config: Arc<Config>,
     messages: Vec<Message>,
     num_ctx: Option<u32>,
-    attempt: u8,
-) -> Result<String> {
-    if attempt > config.max_attempts {
-        return Ok(String::new());
-    }
-
+    format: Option<&Value>,
+    on_chunk: &(dyn Fn(&str) + Send + Sync),
+) -> Result<Completion> {
     let options = Options {
         num_ctx: num_ctx.unwrap_or(DEFAULT_CODE_NUM_CTX),
         temperature: 0.0,
     };
 
-    let model = config
-        .model
-        .clone()
-        .unwrap_or(DEFAULT_CODE_MODEL.to_string());
-
     let ollama_request = OllamaRequest {
+        format,
         keep_alive: config.keep_alive,
-        messages: messages.clone(),
-        model: model.clone(),
+        messages,
+        model: backend::model(&config),
         options,
-        stream: false,
+        stream: config.stream,
     };
 
-    let url = format!("{}/api/chat", config.ollama_host);
+    let url = format!("{}/api/chat", config.host);
 
-    let response = reqwest::ClientBuilder::new()
-        .connect_timeout(Duration::from_secs(config.timeout))
-        .timeout(Duration::from_secs(config.timeout))
-        .build()?
+    let response = backend::client(&config)?
         .post(url)
         .json(&ollama_request)
         .send()
-        .await;
+        .await?;
+    let response = backend::check_status(response).await?;
 
-    match response {
-        Err(e) => {
-            eprintln!("Error: {e}");
+    if !config.stream {
+        let response_text = response.text().await?;
 
-            let attempt = attempt + 1;
+        let ollama_response: OllamaResponse = serde_json::from_str(&response_text)?;
 
-            let response = request(config, messages, num_ctx, attempt).await;
-
-            return response;
-        }
-        Ok(response) => {
-            if response.status() == StatusCode::CREATED || response.status() == StatusCode::OK {
-                let response_text = response.text().await?;
-
-                let ollama_response: OllamaResponse = serde_json::from_str(&response_text)?;
-
-                let signature = app::signature(&model);
-
-                let response = format!("{}\n\n{signature}", ollama_response.message.content);
-
-                return Ok(response);
-            }
-        }
-    }
-
-    Err(Box::new(Error::OllamaRequestProblem))
-}
-
-pub async fn run_request(
-    config: Arc<Config>,
-    messages: Vec<Message>,
-    start_date: DateTime<Utc>,
-) -> Result<()> {
-    let mut length = 0;
-
-    for message in &messages {
-        length += message.content.len();
+        return Ok(Completion {
+            completion_tokens: ollama_response.eval_count,
+            content: ollama_response.message.content,
+            prompt_tokens: ollama_response.prompt_eval_count,
+        });
     }
 
-    let num_ctx = (u32::try_from(length)? / 4) + 4096;
+    let mut completion = Completion::default();
 
-    println!(
-        "Context window = {num_ctx}\tkeep_alive = {}\ttimeout = {}\n\n",
-        config.keep_alive, config.timeout
-    );
-
-    if let Some(skip_larger) = config.skip_larger
-        && num_ctx > skip_larger
-    {
-        println!("Context too large. Skipping...");
-
-        return Ok(());
-    }
+    backend::read_lines(&config, response, |line| {
+        let ollama_response: OllamaResponse = serde_json::from_str(line)?;
 
-    let result = request(config.clone(), messages.clone(), Some(num_ctx), 1).await?;
+        on_chunk(&ollama_response.message.content);
+        completion
+            .content
+            .push_str(&ollama_response.message.content);
 
-    println!("{result}");
-
-    let end_date = Utc::now();
-
-    let delta = end_date - start_date;
-
-    let task = match config.mode {
-        Mode::Ask => "Answer generated",
-        Mode::Checker => "Checked",
-        Mode::CommitReview => "Commit review generated",
-        Mode::CommitSummary => "Commit summary generated",
-        Mode::ConvertToRust => "Converted",
-        Mode::CriteriaVerify => "Criteria verified",
-        Mode::DesignAdvice => "Design advice generated",
-        Mode::Explain => "Explained",
-        Mode::Performance => "Checked",
-        Mode::TaskGenerate => "Task generated",
-        Mode::TaskReview => "Task review generated",
-    };
+        if ollama_response.done {
+            completion.completion_tokens = ollama_response.eval_count;
+            completion.prompt_tokens = ollama_response.prompt_eval_count;
+        }
 
-    println!("\n\n{task} in {} seconds.\n", delta.num_seconds());
+        Ok(ollama_response.done)
+    })
+    .await?;
 
-    Ok(())
+    Ok(completion)
 }
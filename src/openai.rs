@@ -0,0 +1,168 @@
+use crate::{
+    Result,
+    backend::{self, Completion, Message},
+    config::Config,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::sync::Arc;
+
+#[derive(Debug, Serialize)]
+pub struct OpenAiRequest {
+    pub messages: Vec<Message>,
+    pub model: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<Value>,
+    pub stream: bool,
+    pub temperature: f32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OpenAiModel {
+    id: String,
+    #[serde(default)]
+    max_model_len: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OpenAiModels {
+    data: Vec<OpenAiModel>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OpenAiResponse {
+    #[serde(default)]
+    choices: Vec<Choice>,
+    #[serde(default)]
+    usage: Option<Usage>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Choice {
+    #[serde(default)]
+    delta: Option<Delta>,
+    #[serde(default)]
+    message: Option<Message>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Usage {
+    completion_tokens: u32,
+    prompt_tokens: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Delta {
+    #[serde(default)]
+    content: Option<String>,
+}
+
+pub async fn context_length(config: &Config) -> Result<Option<u32>> {
+    let model = backend::model(config);
+
+    let url = format!("{}/models", config.host);
+
+    let mut request_builder = backend::client(config)?.get(url);
+
+    if let Some(api_key) = &config.api_key {
+        request_builder = request_builder.bearer_auth(api_key);
+    }
+
+    let response = request_builder.send().await?;
+    let response = backend::check_status(response).await?;
+
+    let openai_models: OpenAiModels = response.json().await?;
+
+    let context_length = openai_models
+        .data
+        .into_iter()
+        .find(|openai_model| openai_model.id == model)
+        .and_then(|openai_model| openai_model.max_model_len);
+
+    Ok(context_length)
+}
+
+pub async fn request(
+    config: Arc<Config>,
+    messages: Vec<Message>,
+    format: Option<&Value>,
+    on_chunk: &(dyn Fn(&str) + Send + Sync),
+) -> Result<Completion> {
+    let openai_request = OpenAiRequest {
+        messages,
+        model: backend::model(&config),
+        response_format: format.map(|schema| {
+            json!({
+                "type": "json_schema",
+                "json_schema": { "name": "response", "schema": schema }
+            })
+        }),
+        stream: config.stream,
+        temperature: 0.0,
+    };
+
+    let url = format!("{}/chat/completions", config.host);
+
+    let mut request_builder = backend::client(&config)?.post(url).json(&openai_request);
+
+    if let Some(api_key) = &config.api_key {
+        request_builder = request_builder.bearer_auth(api_key);
+    }
+
+    let response = request_builder.send().await?;
+    let response = backend::check_status(response).await?;
+
+    if !config.stream {
+        let response_text = response.text().await?;
+
+        let openai_response: OpenAiResponse = serde_json::from_str(&response_text)?;
+
+        let content = openai_response
+            .choices
+            .into_iter()
+            .filter_map(|choice| choice.message)
+            .map(|message| message.content)
+            .collect::<String>();
+
+        return Ok(Completion {
+            completion_tokens: openai_response
+                .usage
+                .as_ref()
+                .map(|usage| usage.completion_tokens),
+            content,
+            prompt_tokens: openai_response.usage.map(|usage| usage.prompt_tokens),
+        });
+    }
+
+    let mut completion = Completion::default();
+
+    backend::read_lines(&config, response, |line| {
+        let Some(data) = line.strip_prefix("data:") else {
+            return Ok(false);
+        };
+        let data = data.trim();
+
+        if data == "[DONE]" {
+            return Ok(true);
+        }
+
+        let openai_response: OpenAiResponse = serde_json::from_str(data)?;
+
+        for choice in openai_response.choices {
+            if let Some(chunk) = choice.delta.and_then(|delta| delta.content) {
+                on_chunk(&chunk);
+                completion.content.push_str(&chunk);
+            }
+        }
+
+        if let Some(usage) = openai_response.usage {
+            completion.completion_tokens = Some(usage.completion_tokens);
+            completion.prompt_tokens = Some(usage.prompt_tokens);
+        }
+
+        Ok(false)
+    })
+    .await?;
+
+    Ok(completion)
+}
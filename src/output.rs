@@ -0,0 +1,381 @@
+use crate::{
+    Result, app, backend,
+    config::Config,
+    error::Error,
+    file::Source,
+    finding::{self, Finding, Severity},
+    html,
+    mode::{Mode, ModeResult},
+    sarif,
+};
+use serde::Serialize;
+use std::{
+    cell::RefCell,
+    io::{Write, stderr, stdout},
+    str::FromStr,
+};
+
+tokio::task_local! {
+    static BUFFER: RefCell<Vec<(Stream, String)>>;
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Format {
+    Html,
+    Json,
+    Sarif,
+    Text,
+}
+
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let lowercase = s.to_string().to_lowercase();
+        let s = lowercase.as_str();
+        match s {
+            "html" => Ok(Format::Html),
+            "json" => Ok(Format::Json),
+            "sarif" => Ok(Format::Sarif),
+            "text" => Ok(Format::Text),
+            _ => Err(Error::UnknownFormat(s.to_string())),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Stream {
+    Progress,
+    Warning,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct FileReport {
+    pub file_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub findings: Option<Vec<Finding>>,
+    pub results: Vec<ModeResult>,
+    pub skipped: bool,
+    #[serde(skip)]
+    pub source: Option<Source>,
+    pub text: Option<String>,
+}
+
+impl FileReport {
+    pub fn new(mode: &Mode, file_name: Option<String>, parts: Vec<Part>) -> Self {
+        let skipped = parts.iter().all(|part| part.result.text.is_none());
+
+        let findings = if finding::has_findings(mode) && !skipped {
+            let mut findings = vec![];
+
+            for part in &parts {
+                let Some(text) = &part.result.text else {
+                    continue;
+                };
+
+                for finding in finding::parse(text) {
+                    findings.push(part.locate(finding, file_name.as_deref()));
+                }
+            }
+
+            Some(findings)
+        } else {
+            None
+        };
+
+        let text = if skipped {
+            None
+        } else {
+            Some(
+                parts
+                    .iter()
+                    .filter_map(|part| part.result.text.clone())
+                    .collect::<Vec<String>>()
+                    .join("\n\n"),
+            )
+        };
+
+        Self {
+            file_name,
+            findings,
+            results: parts.into_iter().map(|part| part.result).collect(),
+            skipped,
+            source: None,
+            text,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Part {
+    pub first_line: u32,
+    pub last_line: Option<u32>,
+    pub line_numbers: bool,
+    pub result: ModeResult,
+}
+
+impl Part {
+    pub fn new(result: ModeResult) -> Self {
+        Self {
+            first_line: 1,
+            last_line: None,
+            line_numbers: false,
+            result,
+        }
+    }
+
+    pub fn locate(&self, mut finding: Finding, file_name: Option<&str>) -> Finding {
+        if !self.line_numbers {
+            let offset = self.first_line.saturating_sub(1);
+            let overflow = [finding.start_line, finding.end_line]
+                .into_iter()
+                .flatten()
+                .any(|line| line.checked_add(offset).is_none());
+
+            if overflow {
+                finding.start_line = None;
+                finding.end_line = None;
+
+                return finding;
+            }
+
+            finding.start_line = finding.start_line.map(|line| line + offset);
+            finding.end_line = finding.end_line.map(|line| line + offset);
+        }
+
+        let start_line = finding.start_line.unwrap_or_default();
+        let end_line = finding.end_line.unwrap_or(start_line);
+        let last_line = self.last_line.unwrap_or(u32::MAX);
+
+        if start_line < self.first_line || end_line < start_line || end_line > last_line {
+            finding.start_line = None;
+            finding.end_line = None;
+
+            return finding;
+        }
+
+        finding.end_line = Some(end_line);
+
+        if let Some(file_name) = file_name {
+            finding.location = Some(format!("{file_name}:{start_line}"));
+        }
+
+        finding
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub files: Vec<FileReport>,
+    pub mode: String,
+    pub model: String,
+    pub signature: String,
+}
+
+impl Report {
+    pub fn new(config: &Config) -> Self {
+        let model = backend::model(config);
+
+        Self {
+            files: vec![],
+            mode: config.mode.to_string(),
+            signature: app::signature(&model),
+            model,
+        }
+    }
+
+    pub fn has_findings(&self, fail_on: Severity) -> bool {
+        self.files
+            .iter()
+            .flat_map(|file_report| file_report.findings.iter().flatten())
+            .any(|finding| finding.severity.unwrap_or(Severity::Medium) >= fail_on)
+    }
+}
+
+pub async fn capture<F: Future>(future: F) -> (F::Output, Vec<(Stream, String)>) {
+    BUFFER
+        .scope(RefCell::new(vec![]), async {
+            let result = future.await;
+            let buffer = BUFFER.with(|buffer| buffer.take());
+
+            (result, buffer)
+        })
+        .await
+}
+
+pub fn flush(config: &Config, text: &str) {
+    if is_stdout_text(config) {
+        let mut stdout = stdout().lock();
+        let _ = stdout.write_all(text.as_bytes());
+        let _ = stdout.flush();
+    } else {
+        let mut stderr = stderr().lock();
+        let _ = stderr.write_all(text.as_bytes());
+    }
+}
+
+pub fn flush_buffer(config: &Config, buffer: &[(Stream, String)]) {
+    for (stream, text) in buffer {
+        match stream {
+            Stream::Progress => flush(config, text),
+            Stream::Warning => flush_warning(text),
+        }
+    }
+}
+
+pub fn chunk(config: &Config, text: &str) {
+    if config.stream && prints_text(config) {
+        print(config, text);
+    }
+}
+
+pub fn discard(config: &Config, mark: usize) {
+    let _ = BUFFER.try_with(|buffer| {
+        let mut buffer = buffer.borrow_mut();
+        let mark = mark.min(buffer.len());
+        let discarded = buffer.split_off(mark);
+
+        buffer.extend(
+            discarded
+                .into_iter()
+                .filter(|(stream, _)| *stream == Stream::Warning),
+        );
+    });
+
+    progress(config, "\n\n--- Retrying, previous output discarded ---\n");
+}
+
+pub fn finished(config: &Config, result: &ModeResult) {
+    if result.skipped {
+        progress(config, "Context too large. Skipping...");
+
+        return;
+    }
+
+    if prints_text(config) {
+        let signature = app::signature(&result.model);
+
+        if config.stream {
+            print(config, &format!("\n\n{signature}\n"));
+        } else {
+            let text = result.text.as_deref().unwrap_or_default();
+
+            print(config, &format!("{text}\n\n{signature}\n"));
+        }
+    }
+
+    let task = match config.mode {
+        Mode::Ask => "Answer generated",
+        Mode::Checker => "Checked",
+        Mode::CommitReview => "Commit review generated",
+        Mode::CommitSummary => "Commit summary generated",
+        Mode::ConvertToRust => "Converted",
+        Mode::CriteriaVerify => "Criteria verified",
+        Mode::DesignAdvice => "Design advice generated",
+        Mode::DocGenerate => "Doc comments generated",
+        Mode::Explain => "Explained",
+        Mode::Performance => "Checked",
+        Mode::TaskGenerate => "Task generated",
+        Mode::TaskReview => "Task review generated",
+        Mode::TestGenerate => "Tests generated",
+    };
+
+    progress(
+        config,
+        &format!("\n\n{task} in {} seconds.\n", result.seconds()),
+    );
+}
+
+pub fn is_stdout_text(config: &Config) -> bool {
+    config.format == Format::Text && config.output.is_none() && config.output_dir.is_none()
+}
+
+pub fn locations(config: &Config, file_report: &FileReport) {
+    let locations = locations_text(file_report);
+
+    if !locations.is_empty() {
+        print(config, &format!("\n{locations}"));
+    }
+}
+
+pub fn locations_text(file_report: &FileReport) -> String {
+    let locations = file_report
+        .findings
+        .iter()
+        .flatten()
+        .filter_map(|finding| {
+            let location = finding.location.as_ref()?;
+
+            Some(format!("{location}: {}\n", finding.summary))
+        })
+        .collect::<String>();
+
+    if locations.is_empty() {
+        return locations;
+    }
+
+    format!("Locations:\n{locations}")
+}
+
+pub fn mark() -> usize {
+    BUFFER
+        .try_with(|buffer| buffer.borrow().len())
+        .unwrap_or_default()
+}
+
+pub fn print(config: &Config, text: &str) {
+    if !is_stdout_text(config) {
+        return;
+    }
+
+    write(config, Stream::Progress, text);
+}
+
+pub fn prints_text(config: &Config) -> bool {
+    !matches!(config.mode, Mode::DocGenerate)
+}
+
+pub fn progress(config: &Config, text: &str) {
+    write(config, Stream::Progress, &format!("{text}\n"));
+}
+
+pub fn render(config: &Config, report: &Report) -> Result<Option<String>> {
+    let rendered = match config.format {
+        Format::Html => html::render(&[report]),
+        Format::Json => serde_json::to_string_pretty(report)?,
+        Format::Sarif => serde_json::to_string_pretty(&sarif::render(report))?,
+        Format::Text => return Ok(None),
+    };
+
+    Ok(Some(rendered))
+}
+
+pub fn started(config: &Config, result: &ModeResult) {
+    progress(
+        config,
+        &format!(
+            "Context window = {}\tPrompt tokens = {}\tkeep_alive = {}\ttimeout = {}\n\n",
+            result.num_ctx, result.estimated_prompt_tokens, config.keep_alive, config.timeout
+        ),
+    );
+}
+
+pub fn warn(config: &Config, text: &str) {
+    write(config, Stream::Warning, &format!("{text}\n"));
+}
+
+fn flush_warning(text: &str) {
+    let mut stderr = stderr().lock();
+    let _ = stderr.write_all(text.as_bytes());
+}
+
+fn write(config: &Config, stream: Stream, text: &str) {
+    let buffered = BUFFER.try_with(|buffer| buffer.borrow_mut().push((stream, text.to_string())));
+
+    if buffered.is_err() {
+        match stream {
+            Stream::Progress => flush(config, text),
+            Stream::Warning => flush_warning(text),
+        }
+    }
+}
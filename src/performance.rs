@@ -1,7 +1,10 @@
 use crate::{
     Result,
+    backend::{self, Message},
     config::Config,
-    ollama::{self, Message},
+    file::Source,
+    mode::ModeResult,
+    prompt::{self, Vars},
 };
 use chrono::Utc;
 use std::sync::Arc;
@@ -53,10 +56,12 @@ Use this exact template for each reported problem:
 ==========
 Problem summary
 
+Severity: critical | high | medium | low
+(Exactly one of these four words)
+
 Problem detailed description
 - Why this affects performance
 - What patterns or inputs make it worse
-- Estimated impact (high / medium / low)
 
 Recommended fix
 
@@ -65,25 +70,39 @@ Optional code example
 
 Output only your findings in the required format. No commentary outside the template.";
 
-pub async fn run(config: Arc<Config>, code: &str) -> Result<()> {
+pub const USER_TEMPLATE: &str = "Here is the {language} code: {code}";
+
+pub async fn run(config: Arc<Config>, source: &Source) -> Result<ModeResult> {
     let start_date = Utc::now();
 
+    let vars = Vars {
+        file_name: &source.name,
+        language: &source.language,
+        ..Vars::new(&config, &source.code)
+    };
+
     let mut messages = vec![];
 
     let message = Message {
-        content: SYSTEM_PROMPT.to_string(),
+        content: prompt::system(&config, SYSTEM_PROMPT)?,
         role: "system".to_string(),
     };
     messages.push(message);
 
-    let prompt = format!("Here is the code: {code}");
+    if let Some(context) = &source.context {
+        let message = Message {
+            content: context.clone(),
+            role: "user".to_string(),
+        };
+        messages.push(message);
+    }
+
+    let prompt = prompt::user(&config, USER_TEMPLATE, &vars)?;
     let message = Message {
         content: prompt.clone(),
         role: "user".to_string(),
     };
     messages.push(message);
 
-    ollama::run_request(config, messages, start_date).await?;
-
-    Ok(())
+    backend::run_request(config, messages, start_date).await
 }
@@ -0,0 +1,118 @@
+use crate::{
+    Result, ask, checker, commit_review, commit_summary, config::Config, convert_to_rust,
+    criteria_verify, design_advice, doc_generate, explain, mode::Mode, performance, task_generate,
+    task_review, test_generate,
+};
+use std::fs::read_to_string;
+
+#[derive(Clone, Debug, Default)]
+pub struct Vars<'a> {
+    pub code: &'a str,
+    pub criteria: &'a str,
+    pub file_name: &'a str,
+    pub language: &'a str,
+    pub question: &'a str,
+}
+
+impl<'a> Vars<'a> {
+    pub fn new(config: &'a Config, code: &'a str) -> Self {
+        Self {
+            code,
+            question: config.question.as_deref().unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+}
+
+pub fn builtin(mode: &Mode) -> (&'static str, &'static str) {
+    match mode {
+        Mode::Ask => (ask::SYSTEM_PROMPT, ask::USER_TEMPLATE),
+        Mode::Checker => (checker::SYSTEM_PROMPT, checker::USER_TEMPLATE),
+        Mode::CommitReview => (commit_review::SYSTEM_PROMPT, commit_review::USER_TEMPLATE),
+        Mode::CommitSummary => (commit_summary::SYSTEM_PROMPT, commit_summary::USER_TEMPLATE),
+        Mode::ConvertToRust => (
+            convert_to_rust::SYSTEM_PROMPT,
+            convert_to_rust::USER_TEMPLATE,
+        ),
+        Mode::CriteriaVerify => (
+            criteria_verify::SYSTEM_PROMPT,
+            criteria_verify::USER_TEMPLATE,
+        ),
+        Mode::DesignAdvice => (design_advice::SYSTEM_PROMPT, design_advice::USER_TEMPLATE),
+        Mode::DocGenerate => (doc_generate::SYSTEM_PROMPT, doc_generate::USER_TEMPLATE),
+        Mode::Explain => (explain::SYSTEM_PROMPT, explain::USER_TEMPLATE),
+        Mode::Performance => (performance::SYSTEM_PROMPT, performance::USER_TEMPLATE),
+        Mode::TaskGenerate => (task_generate::SYSTEM_PROMPT, task_generate::USER_TEMPLATE),
+        Mode::TaskReview => (task_review::SYSTEM_PROMPT, task_review::USER_TEMPLATE),
+        Mode::TestGenerate => (test_generate::SYSTEM_PROMPT, test_generate::USER_TEMPLATE),
+    }
+}
+
+pub fn render(template: &str, vars: &Vars) -> String {
+    let mut result = String::with_capacity(template.len() + vars.code.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let Some(end) = rest.find('}') else {
+            break;
+        };
+
+        let value = match &rest[1..end] {
+            "code" => Some(vars.code),
+            "criteria" => Some(vars.criteria),
+            "file_name" => Some(vars.file_name),
+            "language" => Some(vars.language),
+            "question" => Some(vars.question),
+            _ => None,
+        };
+
+        match value {
+            Some(value) => {
+                result.push_str(value);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                result.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    result.push_str(rest);
+
+    result
+}
+
+pub fn show(mode: &Mode) {
+    let (system_prompt, user_template) = builtin(mode);
+
+    println!("System prompt:\n\n{system_prompt}\n\nUser template:\n\n{user_template}");
+}
+
+pub fn system(config: &Config, default: &str) -> Result<String> {
+    let mut result = match &config.system_prompt {
+        Some(path) => read_to_string(path)?,
+        None => default.to_string(),
+    };
+
+    if let Some(path) = &config.system_prompt_append {
+        let append = read_to_string(path)?;
+
+        result.push_str("\n\n");
+        result.push_str(append.trim_end());
+    }
+
+    Ok(result)
+}
+
+pub fn user(config: &Config, default: &str, vars: &Vars) -> Result<String> {
+    let template = match &config.prompt_template {
+        Some(path) => read_to_string(path)?,
+        None => default.to_string(),
+    };
+
+    Ok(render(&template, vars))
+}
@@ -0,0 +1,117 @@
+use crate::{
+    finding::{Finding, Severity},
+    output::{FileReport, Report},
+};
+use serde_json::{Value, json};
+
+pub const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+pub const SARIF_VERSION: &str = "2.1.0";
+
+pub fn level(severity: Option<Severity>) -> &'static str {
+    match severity {
+        Some(Severity::Critical | Severity::High) => "error",
+        Some(Severity::Low) => "note",
+        Some(Severity::Medium) | None => "warning",
+    }
+}
+
+pub fn render(report: &Report) -> Value {
+    let rule_id = rule_id(&report.mode);
+
+    let mut results = vec![];
+
+    for file_report in &report.files {
+        for finding in file_report.findings.iter().flatten() {
+            results.push(result(&rule_id, file_report, finding));
+        }
+    }
+
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": SARIF_VERSION,
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": [{
+                        "id": rule_id,
+                        "name": report.mode,
+                        "shortDescription": { "text": rule_description(&report.mode) }
+                    }]
+                }
+            },
+            "properties": {
+                "model": report.model,
+                "signature": report.signature
+            },
+            "results": results
+        }]
+    })
+}
+
+pub fn rule_id(mode: &str) -> String {
+    match mode {
+        "checker" => "CCW-CHECK".to_string(),
+        "performance" => "CCW-PERFORMANCE".to_string(),
+        mode => format!("CCW-{}", mode.to_uppercase().replace('_', "-")),
+    }
+}
+
+fn result(rule_id: &str, file_report: &FileReport, finding: &Finding) -> Value {
+    let mut message = finding.summary.clone();
+
+    if !finding.description.is_empty() {
+        message.push_str(&format!("\n\n{}", finding.description));
+    }
+
+    if !finding.recommendation.is_empty() {
+        message.push_str(&format!("\n\nRecommendation: {}", finding.recommendation));
+    }
+
+    let mut result = json!({
+        "ruleId": rule_id,
+        "level": level(finding.severity),
+        "message": { "text": message }
+    });
+
+    if let Some(file_name) = &file_report.file_name {
+        let mut physical_location = json!({
+            "artifactLocation": { "uri": uri(file_name) }
+        });
+
+        if let Some(start_line) = finding.start_line {
+            let mut region = json!({ "startLine": start_line });
+
+            if let Some(end_line) = finding.end_line {
+                region["endLine"] = json!(end_line);
+            }
+
+            if let Some(snippet) = &finding.snippet {
+                region["snippet"] = json!({ "text": snippet });
+            }
+
+            physical_location["region"] = region;
+        }
+
+        result["locations"] = json!([{ "physicalLocation": physical_location }]);
+    }
+
+    result
+}
+
+fn rule_description(mode: &str) -> &'static str {
+    match mode {
+        "checker" => "Security and correctness issue reported by the CCW checker.",
+        "commit_review" => "Issue reported by the CCW commit review.",
+        "performance" => "Performance issue reported by the CCW performance check.",
+        _ => "Issue reported by CCW.",
+    }
+}
+
+fn uri(file_name: &str) -> String {
+    file_name
+        .replace('\\', "/")
+        .trim_start_matches("./")
+        .to_string()
+}
@@ -0,0 +1,216 @@
+use crate::{
+    Result,
+    backend::{self, Message},
+    config::Config,
+    file::Source,
+    mode::ModeResult,
+    output::{self, FileReport},
+    prompt::{self, Vars},
+};
+use chrono::Utc;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+pub const SYSTEM_PROMPT: &str = "You are CCW-TEST-GENERATE, a meticulous test engineer. Your role is to write unit tests for the code provided by the user.
+
+The user will provide:
+- A source file, or a fragment of one, and its language.
+
+Your output must follow these rules:
+
+1. Use the idiomatic test framework of the language.
+   - Rust: a `#[cfg(test)] mod tests { use super::*; ... }` module with `#[test]` functions.
+   - Python: pytest test functions using plain `assert` and `pytest.raises`.
+   - Go: a `_test` file in the same package using the `testing` package and table-driven tests.
+   - Java: JUnit 5 test classes with `@Test` methods and `Assertions`.
+   - JavaScript/TypeScript: Jest-style `describe`/`it`/`expect`.
+   - Other languages: the most common unit test framework of the ecosystem.
+
+2. Test the behavior that is visible in the code.
+   - Cover normal cases, edge cases (empty input, boundaries, zero, overflow) and error paths.
+   - Give every test a descriptive name that states the expected behavior.
+   - Keep each test small and focused on one behavior.
+
+3. Stay grounded in the provided code.
+   - Only call functions, types and methods that are present in the code or in the standard library.
+   - Do not invent helpers, fixtures, files or external services.
+   - Skip code that cannot be tested without external resources (network, databases, hardware).
+
+4. Make the tests runnable.
+   - Include the imports the tests need.
+   - Prefer deterministic tests; do not depend on time, randomness or ordering unless the code does.
+
+5. Output only the test code.
+   - Put all tests in ONE fenced code block.
+   - No explanations before or after the code block.
+
+Your goal is to deliver a compact, idiomatic and meaningful test suite for the provided code.";
+
+pub const USER_TEMPLATE: &str = "Here is the {language} code from {file_name}: {code}";
+
+pub async fn run(config: Arc<Config>, source: &Source) -> Result<ModeResult> {
+    let start_date = Utc::now();
+
+    let vars = Vars {
+        file_name: &source.name,
+        language: &source.language,
+        ..Vars::new(&config, &source.code)
+    };
+
+    let mut messages = vec![];
+
+    let message = Message {
+        content: prompt::system(&config, SYSTEM_PROMPT)?,
+        role: "system".to_string(),
+    };
+    messages.push(message);
+
+    if let Some(context) = &source.context {
+        let message = Message {
+            content: context.clone(),
+            role: "user".to_string(),
+        };
+        messages.push(message);
+    }
+
+    let prompt = prompt::user(&config, USER_TEMPLATE, &vars)?;
+    let message = Message {
+        content: prompt.clone(),
+        role: "user".to_string(),
+    };
+    messages.push(message);
+
+    backend::run_request(config, messages, start_date).await
+}
+
+pub fn code_block(text: &str) -> Option<String> {
+    let mut result = None;
+    let mut block: Option<Vec<&str>> = None;
+
+    for line in text.lines() {
+        let fence = line.trim_start().starts_with("```");
+
+        match (&mut block, fence) {
+            (None, true) => block = Some(vec![]),
+            (Some(lines), true) => {
+                let code = lines.join("\n");
+
+                if result
+                    .as_ref()
+                    .is_none_or(|result: &String| code.len() > result.len())
+                {
+                    result = Some(code);
+                }
+                block = None;
+            }
+            (Some(lines), false) => lines.push(line),
+            (None, false) => {}
+        }
+    }
+
+    result.or_else(|| {
+        let text = text.trim();
+
+        (!text.is_empty()).then(|| text.to_string())
+    })
+}
+
+pub fn test_file(file_name: &str, language: &str) -> PathBuf {
+    let path = Path::new(file_name);
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let stem = path
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let extension = path
+        .extension()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+
+    match language {
+        "Rust" if matches!(stem.as_str(), "lib" | "main" | "mod") => dir.join("tests.rs"),
+        "Rust" => dir.join(stem).join("tests.rs"),
+        "Python" => dir.join(format!("test_{stem}.py")),
+        "C#" => dir.join(format!("{stem}Tests.{extension}")),
+        "Java" | "Kotlin" | "Scala" | "Swift" => dir.join(format!("{stem}Test.{extension}")),
+        "JavaScript" | "TypeScript" => dir.join(format!("{stem}.test.{extension}")),
+        _ => dir.join(format!("{stem}_test.{extension}")),
+    }
+}
+
+pub fn unwrap_rust_module(code: &str) -> String {
+    let lines = code.lines().collect::<Vec<&str>>();
+
+    let Some(start) = lines
+        .iter()
+        .position(|line| line.trim_start().starts_with("mod tests"))
+    else {
+        return code.to_string();
+    };
+    let Some(end) = lines.iter().rposition(|line| line.trim() == "}") else {
+        return code.to_string();
+    };
+
+    if end <= start {
+        return code.to_string();
+    }
+
+    let mut result = String::new();
+
+    for line in &lines[start + 1..end] {
+        result.push_str(line.strip_prefix("    ").unwrap_or(line));
+        result.push('\n');
+    }
+
+    result
+}
+
+pub fn write(config: &Config, file_report: &FileReport) -> Result<Option<PathBuf>> {
+    let Some(source) = &file_report.source else {
+        return Ok(None);
+    };
+
+    let mut blocks = vec![];
+
+    for result in &file_report.results {
+        let Some(code) = result.text.as_deref().and_then(code_block) else {
+            continue;
+        };
+
+        if source.language == "Rust" {
+            blocks.push(unwrap_rust_module(&code));
+        } else {
+            blocks.push(format!("{}\n", code.trim_end()));
+        }
+    }
+
+    if blocks.is_empty() {
+        return Ok(None);
+    }
+
+    let path = test_file(&source.name, &source.language);
+
+    if path.exists() {
+        output::warn(
+            config,
+            &format!("Skipping {}: file already exists.", path.display()),
+        );
+
+        return Ok(None);
+    }
+
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)?;
+    }
+
+    fs::write(&path, blocks.join("\n"))?;
+
+    Ok(Some(path))
+}
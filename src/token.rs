@@ -0,0 +1,33 @@
+pub const WORD_CHARS_PER_TOKEN: usize = 4;
+
+pub fn estimate(text: &str) -> usize {
+    let mut result = 0;
+    let mut word: usize = 0;
+    let mut spaces = 0;
+
+    for c in text.chars() {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            word += 1;
+
+            continue;
+        }
+
+        result += word.div_ceil(WORD_CHARS_PER_TOKEN);
+        word = 0;
+
+        if c == ' ' || c == '\t' {
+            spaces += 1;
+
+            continue;
+        }
+
+        if spaces > 1 {
+            result += 1;
+        }
+        spaces = 0;
+
+        result += 1;
+    }
+
+    result + word.div_ceil(WORD_CHARS_PER_TOKEN)
+}
@@ -0,0 +1,263 @@
+mod common;
+
+use common::{DEFAULT_CONTENT, MockServer, Reply, TestDir, ccw, stderr, stdout};
+use std::time::Duration;
+
+const CODE: &str = "fn main() {\n    println!(\"Hello, world!\");\n}\n";
+
+#[test]
+fn streamed_response() {
+    let server = MockServer::start().reply(Reply::Chat("one two three".to_string()));
+    let dir = TestDir::new("streamed-response");
+    dir.write("main.rs", CODE);
+
+    let output = ccw(&server, &dir.path, &["--file=main.rs"], None);
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("one two three"));
+    assert_eq!(server.chat_requests()[0].body["stream"], true);
+}
+
+#[test]
+fn non_streamed_response() {
+    let server = MockServer::start().reply(Reply::Chat("one two three".to_string()));
+    let dir = TestDir::new("non-streamed-response");
+    dir.write("main.rs", CODE);
+
+    let output = ccw(
+        &server,
+        &dir.path,
+        &["--file=main.rs", "--stream=false"],
+        None,
+    );
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("one two three"));
+    assert_eq!(server.chat_requests()[0].body["stream"], false);
+}
+
+#[test]
+fn request_options() {
+    let server = MockServer::start();
+    let dir = TestDir::new("request-options");
+    dir.write("main.rs", CODE);
+
+    let output = ccw(
+        &server,
+        &dir.path,
+        &["--file=main.rs", "--keep-alive=30"],
+        None,
+    );
+
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let body = &server.chat_requests()[0].body;
+    assert_eq!(body["model"], common::MODEL);
+    assert_eq!(body["keep_alive"], 30);
+    assert_eq!(body["options"]["temperature"], 0.0);
+    assert!(body["options"]["num_ctx"].as_u64().unwrap() > 4096);
+}
+
+#[test]
+fn retry_after_server_error() {
+    let server = MockServer::start().reply(Reply::Status(503, "busy".to_string()));
+    let dir = TestDir::new("retry-after-server-error");
+    dir.write("main.rs", CODE);
+
+    let output = ccw(&server, &dir.path, &["--file=main.rs"], None);
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains(DEFAULT_CONTENT));
+    assert!(stderr(&output).contains("Retrying in"));
+    assert_eq!(server.chat_requests().len(), 2);
+}
+
+#[test]
+fn retry_after_stalled_stream() {
+    let server = MockServer::start().reply(Reply::Stall(Duration::from_secs(3)));
+    let dir = TestDir::new("retry-after-stalled-stream");
+    dir.write("main.rs", CODE);
+
+    let output = ccw(
+        &server,
+        &dir.path,
+        &["--file=main.rs", "--stall-timeout=1"],
+        None,
+    );
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains(DEFAULT_CONTENT));
+    assert_eq!(server.chat_requests().len(), 2);
+}
+
+#[test]
+fn retry_discards_partial_output() {
+    let server = MockServer::start()
+        .reply(Reply::Partial(
+            "Half answer".to_string(),
+            Duration::from_secs(3),
+        ))
+        .reply(Reply::Partial(
+            "Half answer".to_string(),
+            Duration::from_secs(3),
+        ));
+    let dir = TestDir::new("retry-discards-partial-output");
+    dir.write("main.rs", CODE);
+
+    let output = ccw(
+        &server,
+        &dir.path,
+        &["--file=main.rs", "--stall-timeout=1", "--no-cache"],
+        None,
+    );
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("Half answer"));
+    assert!(stdout(&output).contains("Retrying, previous output discarded"));
+    assert!(stdout(&output).contains(DEFAULT_CONTENT));
+
+    let output = ccw(
+        &server,
+        &dir.path,
+        &[
+            "--file=main.rs",
+            "--stall-timeout=1",
+            "--no-cache",
+            "--jobs=2",
+        ],
+        None,
+    );
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(!stdout(&output).contains("Half answer"));
+    assert!(stdout(&output).contains(DEFAULT_CONTENT));
+}
+
+#[test]
+fn retry_warning_with_jobs() {
+    let server = MockServer::start().reply(Reply::Status(503, "busy".to_string()));
+    let dir = TestDir::new("retry-warning-with-jobs");
+    dir.write("main.rs", CODE);
+
+    let output = ccw(&server, &dir.path, &["--file=main.rs", "--jobs=2"], None);
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stderr(&output).contains("Retrying in"));
+    assert!(!stdout(&output).contains("Retrying in"));
+    assert!(stdout(&output).contains(DEFAULT_CONTENT));
+}
+
+#[test]
+fn delayed_response() {
+    let server = MockServer::start().reply(Reply::Delay(
+        Duration::from_millis(500),
+        Box::new(Reply::Chat("late answer".to_string())),
+    ));
+    let dir = TestDir::new("delayed-response");
+    dir.write("main.rs", CODE);
+
+    let output = ccw(&server, &dir.path, &["--file=main.rs"], None);
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("late answer"));
+    assert_eq!(server.chat_requests().len(), 1);
+}
+
+#[test]
+fn no_retry_after_client_error() {
+    let server = MockServer::start().reply(Reply::Status(
+        404,
+        "{\"error\":\"model not found\"}".to_string(),
+    ));
+    let dir = TestDir::new("no-retry-after-client-error");
+    dir.write("main.rs", CODE);
+
+    let output = ccw(&server, &dir.path, &["--file=main.rs"], None);
+
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("status 404"));
+    assert!(stderr(&output).contains("model not found"));
+    assert_eq!(server.chat_requests().len(), 1);
+}
+
+#[test]
+fn attempts_exhausted() {
+    let server = MockServer::start()
+        .reply(Reply::Status(500, "first".to_string()))
+        .reply(Reply::Status(500, "second".to_string()));
+    let dir = TestDir::new("attempts-exhausted");
+    dir.write("main.rs", CODE);
+
+    let output = ccw(
+        &server,
+        &dir.path,
+        &["--file=main.rs", "--max-attempts=2"],
+        None,
+    );
+
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("request failed after 2 attempts"));
+    assert!(stderr(&output).contains("second"));
+    assert_eq!(server.chat_requests().len(), 2);
+}
+
+#[test]
+fn skip_larger() {
+    let server = MockServer::start();
+    let dir = TestDir::new("skip-larger");
+    dir.write("main.rs", CODE);
+
+    let output = ccw(
+        &server,
+        &dir.path,
+        &["--file=main.rs", "--skip-larger=100"],
+        None,
+    );
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("Context too large. Skipping..."));
+    assert!(server.chat_requests().is_empty());
+}
+
+#[test]
+fn context_length_clamps_num_ctx() {
+    let server = MockServer::start().context_length(4608);
+    let dir = TestDir::new("context-length-clamps-num-ctx");
+    dir.write("main.rs", CODE);
+
+    let output = ccw(&server, &dir.path, &["--file=main.rs"], None);
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(server.chat_requests()[0].body["options"]["num_ctx"], 4608);
+}
+
+#[test]
+fn context_too_large() {
+    let server = MockServer::start().context_length(1024);
+    let dir = TestDir::new("context-too-large");
+    dir.write("main.rs", CODE);
+
+    let output = ccw(&server, &dir.path, &["--file=main.rs"], None);
+
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("model context length is 1024"));
+    assert!(server.chat_requests().is_empty());
+}
+
+#[test]
+fn unknown_backend() {
+    let server = MockServer::start();
+    let dir = TestDir::new("unknown-backend");
+    dir.write("main.rs", CODE);
+
+    let output = ccw(
+        &server,
+        &dir.path,
+        &["--file=main.rs", "--backend=vllm"],
+        None,
+    );
+
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("unknown backend vllm"));
+    assert!(server.chat_requests().is_empty());
+}
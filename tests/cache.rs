@@ -0,0 +1,108 @@
+mod common;
+
+use common::{DEFAULT_CONTENT, MockServer, TestDir, ccw, stderr, stdout};
+
+const CODE: &str = "fn main() {\n    println!(\"Hello, world!\");\n}\n";
+
+#[test]
+fn unchanged_file_reuses_result() {
+    let server = MockServer::start();
+    let dir = TestDir::new("cache-reuse");
+    dir.write("main.rs", CODE);
+
+    let first = ccw(&server, &dir.path, &["--file=main.rs"], None);
+    let second = ccw(&server, &dir.path, &["--file=main.rs"], None);
+
+    assert!(second.status.success(), "{}", stderr(&second));
+    assert!(stdout(&first).contains(DEFAULT_CONTENT));
+    assert!(stdout(&second).contains(DEFAULT_CONTENT));
+    assert!(stdout(&second).contains("Using cached result."));
+    assert_eq!(server.chat_requests().len(), 1);
+}
+
+#[test]
+fn identical_files_share_result() {
+    let server = MockServer::start();
+    let dir = TestDir::new("cache-identical-files");
+    dir.write("src/a.rs", CODE);
+    dir.write("src/b.rs", CODE);
+
+    let output = ccw(&server, &dir.path, &["--dir=src"], None);
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output).matches(DEFAULT_CONTENT).count(), 2);
+    assert_eq!(stdout(&output).matches("Using cached result.").count(), 1);
+    assert_eq!(server.chat_requests().len(), 1);
+}
+
+#[test]
+fn changed_inputs_miss() {
+    let server = MockServer::start();
+    let dir = TestDir::new("cache-miss");
+    dir.write("main.rs", CODE);
+
+    ccw(&server, &dir.path, &["--file=main.rs"], None);
+    ccw(
+        &server,
+        &dir.path,
+        &["--file=main.rs", "--mode=explain"],
+        None,
+    );
+    dir.write("main.rs", "fn main() {}\n");
+    ccw(&server, &dir.path, &["--file=main.rs"], None);
+
+    assert_eq!(server.chat_requests().len(), 3);
+}
+
+#[test]
+fn no_cache() {
+    let server = MockServer::start();
+    let dir = TestDir::new("cache-disabled");
+    dir.write("main.rs", CODE);
+
+    ccw(&server, &dir.path, &["--file=main.rs", "--no-cache"], None);
+    ccw(&server, &dir.path, &["--file=main.rs", "--no-cache"], None);
+
+    assert_eq!(server.chat_requests().len(), 2);
+    assert!(!dir.path.join(".cache/ccw").exists());
+}
+
+#[test]
+fn clear() {
+    let server = MockServer::start();
+    let dir = TestDir::new("cache-clear");
+    dir.write("main.rs", CODE);
+
+    ccw(&server, &dir.path, &["--file=main.rs"], None);
+
+    let output = ccw(&server, &dir.path, &["cache", "clear"], None);
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("Removed 1 cached results"));
+
+    ccw(&server, &dir.path, &["--file=main.rs"], None);
+
+    assert_eq!(server.chat_requests().len(), 2);
+}
+
+#[test]
+fn max_size() {
+    let server = MockServer::start();
+    let dir = TestDir::new("cache-max-size");
+    dir.write("main.rs", CODE);
+
+    ccw(
+        &server,
+        &dir.path,
+        &["--file=main.rs", "--cache-max-size=0"],
+        None,
+    );
+    ccw(
+        &server,
+        &dir.path,
+        &["--file=main.rs", "--cache-max-size=0"],
+        None,
+    );
+
+    assert_eq!(server.chat_requests().len(), 2);
+}
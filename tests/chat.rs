@@ -0,0 +1,73 @@
+mod common;
+
+use common::{DEFAULT_CONTENT, MockServer, TestDir, ccw, stderr, stdout};
+use std::fs;
+
+#[test]
+fn keeps_history_and_switches_model() {
+    let server = MockServer::start();
+    let dir = TestDir::new("chat-history");
+    dir.write("main.rs", "fn main() {}\n");
+
+    let input = "/add main.rs\nWhat does it do?\nWhy?\n/model other-coder:2b\nAnything else?\n/save chat.json\n/exit\n";
+    let output = ccw(&server, &dir.path, &["chat"], Some(input));
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("Added main.rs"));
+    assert!(stdout(&output).contains(DEFAULT_CONTENT));
+
+    let requests = server.chat_requests();
+    assert_eq!(requests.len(), 3);
+
+    let messages = requests[1].messages();
+    assert!(messages[0].1.contains("CCW-ASK"));
+    assert!(
+        messages[1]
+            .1
+            .contains("Here is the Rust code from main.rs:\nfn main() {}")
+    );
+    assert_eq!(
+        messages[2],
+        ("user".to_string(), "What does it do?".to_string())
+    );
+    assert_eq!(
+        messages[3],
+        ("assistant".to_string(), DEFAULT_CONTENT.to_string())
+    );
+    assert_eq!(messages[4], ("user".to_string(), "Why?".to_string()));
+
+    assert_eq!(requests[1].body["model"], common::MODEL);
+    assert_eq!(requests[2].body["model"], "other-coder:2b");
+    assert_eq!(requests[2].messages().len(), 7);
+
+    let transcript: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(dir.path.join("chat.json")).unwrap()).unwrap();
+    assert_eq!(transcript["model"], "other-coder:2b");
+    assert_eq!(transcript["messages"].as_array().unwrap().len(), 8);
+}
+
+#[test]
+fn loads_transcript() {
+    let server = MockServer::start();
+    let dir = TestDir::new("chat-load");
+    dir.write(
+        "chat.json",
+        "{\"mode\":\"explain\",\"model\":\"mock-coder:1b\",\"messages\":[{\"role\":\"system\",\"content\":\"Be brief.\"},{\"role\":\"user\",\"content\":\"Earlier question\"},{\"role\":\"assistant\",\"content\":\"Earlier answer\"}]}",
+    );
+
+    let output = ccw(
+        &server,
+        &dir.path,
+        &["--mode=explain", "chat", "--load=chat.json"],
+        Some("Follow-up\n"),
+    );
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("Loaded 3 messages"));
+
+    let messages = server.chat_requests()[0].messages();
+    assert_eq!(messages.len(), 4);
+    assert_eq!(messages[0].1, "Be brief.");
+    assert_eq!(messages[2].1, "Earlier answer");
+    assert_eq!(messages[3].1, "Follow-up");
+}
@@ -0,0 +1,113 @@
+mod common;
+
+use ccw::{Client, Config, Mode, Source};
+use common::{DEFAULT_CONTENT, MockServer, Reply};
+use std::time::Duration;
+
+fn config(server: &MockServer) -> Config {
+    Config {
+        cache: false,
+        host: server.url.clone(),
+        model: Some(common::MODEL.to_string()),
+        ..Config::default()
+    }
+}
+
+#[tokio::test]
+async fn check_returns_findings() {
+    let content = "{\"findings\":[{\"summary\":\"Unchecked input\",\"description\":\"Panics\",\"recommendation\":\"Validate\",\"severity\":\"high\",\"start_line\":1,\"end_line\":1}]}";
+    let server = MockServer::start().reply(Reply::Chat(content.to_string()));
+    let client = Client::new(config(&server));
+    let source = Source::new("main.rs", "Rust", "fn main() {}\n");
+
+    let file_report = client.check(&source).await.unwrap();
+
+    assert_eq!(file_report.file_name.as_deref(), Some("main.rs"));
+    let findings = file_report.findings.unwrap();
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].summary, "Unchecked input");
+}
+
+#[tokio::test]
+async fn commit_summary_returns_text() {
+    let server = MockServer::start();
+    let client = Client::new(config(&server));
+
+    let file_report = client.commit_summary("+fn main() {}\n").await.unwrap();
+
+    assert_eq!(file_report.text.as_deref(), Some(DEFAULT_CONTENT));
+    assert!(file_report.findings.is_none());
+    assert!(server.chat_requests()[0].text().contains("+fn main() {}"));
+}
+
+#[tokio::test]
+async fn ask_uses_question() {
+    let server = MockServer::start();
+    let client = Client::new(config(&server));
+
+    let file_report = client.ask("What is a trait?").await.unwrap();
+
+    assert_eq!(file_report.text.as_deref(), Some(DEFAULT_CONTENT));
+    assert!(
+        server.chat_requests()[0]
+            .text()
+            .contains("What is a trait?")
+    );
+}
+
+#[tokio::test]
+async fn with_mode_keeps_config() {
+    let server = MockServer::start();
+    let client = Client::new(Config {
+        keep_alive: 42,
+        ..config(&server)
+    });
+
+    let client = client.with_mode(Mode::Performance);
+
+    assert!(matches!(client.config().mode, Mode::Performance));
+    assert_eq!(client.config().keep_alive, 42);
+}
+
+#[tokio::test]
+async fn explain_returns_results() {
+    let server = MockServer::start();
+    let client = Client::new(config(&server));
+    let source = Source::new("main.rs", "Rust", "fn main() {}\n");
+
+    let file_report = client.explain(&source).await.unwrap();
+
+    assert_eq!(file_report.results.len(), 1);
+    let result = &file_report.results[0];
+    assert_eq!(result.model, common::MODEL);
+    assert_eq!(result.prompt_tokens, Some(10));
+    assert_eq!(result.completion_tokens, Some(5));
+    assert!(!result.skipped);
+    assert!(result.finished_at >= result.started_at);
+}
+
+#[tokio::test]
+async fn clients_keep_their_timeouts() {
+    let server = MockServer::start().reply(Reply::Delay(
+        Duration::from_secs(3),
+        Box::new(Reply::Chat(DEFAULT_CONTENT.to_string())),
+    ));
+    let source = Source::new("main.rs", "Rust", "fn main() {}\n");
+
+    let patient = Client::new(Config {
+        timeout: 30,
+        ..config(&server)
+    });
+    assert!(patient.explain(&source).await.is_ok());
+
+    let server = MockServer::start().reply(Reply::Delay(
+        Duration::from_secs(3),
+        Box::new(Reply::Chat(DEFAULT_CONTENT.to_string())),
+    ));
+    let impatient = Client::new(Config {
+        max_attempts: 1,
+        timeout: 1,
+        ..config(&server)
+    });
+    assert!(impatient.explain(&source).await.is_err());
+}
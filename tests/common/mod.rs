@@ -0,0 +1,336 @@
+#![allow(dead_code)]
+
+use serde_json::{Value, json};
+use std::{
+    collections::VecDeque,
+    env, fs,
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    process::{Command, Output, Stdio},
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+pub const DEFAULT_CONTENT: &str = "Mock answer";
+pub const MODEL: &str = "mock-coder:1b";
+
+#[derive(Clone, Debug)]
+pub enum Reply {
+    Chat(String),
+    Delay(Duration, Box<Reply>),
+    Partial(String, Duration),
+    Stall(Duration),
+    Status(u16, String),
+}
+
+#[derive(Clone, Debug)]
+pub struct Request {
+    pub body: Value,
+    pub method: String,
+    pub path: String,
+}
+
+impl Request {
+    pub fn messages(&self) -> Vec<(String, String)> {
+        self.body["messages"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|message| {
+                (
+                    message["role"].as_str().unwrap_or_default().to_string(),
+                    message["content"].as_str().unwrap_or_default().to_string(),
+                )
+            })
+            .collect()
+    }
+
+    pub fn text(&self) -> String {
+        self.messages()
+            .into_iter()
+            .map(|(_, content)| content)
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+#[derive(Default)]
+struct State {
+    context_length: Option<u64>,
+    replies: VecDeque<Reply>,
+    requests: Vec<Request>,
+}
+
+pub struct MockServer {
+    pub url: String,
+    state: Arc<Mutex<State>>,
+}
+
+impl MockServer {
+    pub fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let state = Arc::new(Mutex::new(State::default()));
+
+        let server_state = state.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let state = server_state.clone();
+                thread::spawn(move || handle(stream, state));
+            }
+        });
+
+        Self { url, state }
+    }
+
+    pub fn chat_requests(&self) -> Vec<Request> {
+        self.requests()
+            .into_iter()
+            .filter(|request| request.path == "/api/chat")
+            .collect()
+    }
+
+    pub fn context_length(self, context_length: u64) -> Self {
+        self.state.lock().unwrap().context_length = Some(context_length);
+
+        self
+    }
+
+    pub fn reply(self, reply: Reply) -> Self {
+        self.state.lock().unwrap().replies.push_back(reply);
+
+        self
+    }
+
+    pub fn requests(&self) -> Vec<Request> {
+        self.state.lock().unwrap().requests.clone()
+    }
+}
+
+pub struct TestDir {
+    pub path: PathBuf,
+}
+
+impl TestDir {
+    pub fn new(name: &str) -> Self {
+        let path = env::temp_dir().join(format!("ccw-test-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+
+        Self { path }
+    }
+
+    pub fn write(&self, name: &str, content: &str) -> PathBuf {
+        let path = self.path.join(name);
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).unwrap();
+        }
+        fs::write(&path, content).unwrap();
+
+        path
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+pub fn ccw(server: &MockServer, dir: &Path, args: &[&str], stdin: Option<&str>) -> Output {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_ccw"));
+
+    for (key, _) in env::vars() {
+        if key.starts_with("CCW_") || key.starts_with("OPENAI_") || key == "OLLAMA_HOST" {
+            command.env_remove(key);
+        }
+    }
+
+    let mut child = command
+        .arg(format!("--model={MODEL}"))
+        .args(args)
+        .current_dir(dir)
+        .env("HOME", dir)
+        .env("OLLAMA_HOST", &server.url)
+        .env("XDG_CACHE_HOME", dir.join(".cache"))
+        .env("XDG_CONFIG_HOME", dir.join(".config"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut child_stdin = child.stdin.take().unwrap();
+    child_stdin
+        .write_all(stdin.unwrap_or_default().as_bytes())
+        .unwrap();
+    drop(child_stdin);
+
+    child.wait_with_output().unwrap()
+}
+
+pub fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).to_string()
+}
+
+pub fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+fn handle(mut stream: TcpStream, state: Arc<Mutex<State>>) {
+    let Some(request) = read_request(&mut stream) else {
+        return;
+    };
+
+    let (reply, context_length) = {
+        let mut state = state.lock().unwrap();
+        state.requests.push(request.clone());
+
+        let reply = if request.path == "/api/chat" {
+            state.replies.pop_front()
+        } else {
+            None
+        };
+
+        (reply, state.context_length)
+    };
+
+    match request.path.as_str() {
+        "/api/chat" => {
+            let reply = reply.unwrap_or(Reply::Chat(DEFAULT_CONTENT.to_string()));
+            let stream_response = request.body["stream"].as_bool().unwrap_or(true);
+
+            write_reply(&mut stream, reply, stream_response);
+        }
+        "/api/show" => {
+            let mut model_info = json!({ "general.architecture": "mock" });
+
+            if let Some(context_length) = context_length {
+                model_info["mock.context_length"] = json!(context_length);
+            }
+
+            write_json(&mut stream, 200, &json!({ "model_info": model_info }));
+        }
+        "/api/tags" => {
+            write_json(&mut stream, 200, &json!({ "models": [{ "name": MODEL }] }));
+        }
+        _ => write_json(&mut stream, 404, &json!({ "error": "not found" })),
+    }
+}
+
+fn read_request(stream: &mut TcpStream) -> Option<Request> {
+    let mut reader = BufReader::new(stream.try_clone().ok()?);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).ok()?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let path = parts.next()?.to_string();
+
+    let mut content_length = 0;
+
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).ok()?;
+
+        if line.trim().is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = line.split_once(':')
+            && name.eq_ignore_ascii_case("content-length")
+        {
+            content_length = value.trim().parse().ok()?;
+        }
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).ok()?;
+
+    let body = serde_json::from_slice(&body).unwrap_or(Value::Null);
+
+    Some(Request { body, method, path })
+}
+
+fn write_json(stream: &mut TcpStream, status: u16, body: &Value) {
+    write_response(stream, status, &body.to_string());
+}
+
+fn write_reply(stream: &mut TcpStream, reply: Reply, stream_response: bool) {
+    match reply {
+        Reply::Chat(content) if stream_response => {
+            let head = "HTTP/1.1 200 OK\r\nContent-Type: application/x-ndjson\r\nConnection: close\r\n\r\n";
+            if stream.write_all(head.as_bytes()).is_err() {
+                return;
+            }
+
+            for word in content.split_inclusive(' ') {
+                let line = json!({
+                    "message": { "role": "assistant", "content": word },
+                    "done": false
+                });
+                if stream
+                    .write_all(format!("{line}\n").as_bytes())
+                    .and_then(|()| stream.flush())
+                    .is_err()
+                {
+                    return;
+                }
+            }
+
+            let line = json!({
+                "message": { "role": "assistant", "content": "" },
+                "done": true,
+                "prompt_eval_count": 10,
+                "eval_count": 5
+            });
+            let _ = stream.write_all(format!("{line}\n").as_bytes());
+        }
+        Reply::Chat(content) => {
+            let body = json!({
+                "message": { "role": "assistant", "content": content },
+                "done": true,
+                "prompt_eval_count": 10,
+                "eval_count": 5
+            });
+
+            write_response(stream, 200, &body.to_string());
+        }
+        Reply::Delay(delay, reply) => {
+            thread::sleep(delay);
+
+            write_reply(stream, *reply, stream_response);
+        }
+        Reply::Partial(content, delay) => {
+            let head = "HTTP/1.1 200 OK\r\nContent-Type: application/x-ndjson\r\nConnection: close\r\n\r\n";
+            let line = json!({
+                "message": { "role": "assistant", "content": content },
+                "done": false
+            });
+            let _ = stream.write_all(format!("{head}{line}\n").as_bytes());
+            let _ = stream.flush();
+
+            thread::sleep(delay);
+        }
+        Reply::Stall(delay) => {
+            let head = "HTTP/1.1 200 OK\r\nContent-Type: application/x-ndjson\r\nConnection: close\r\n\r\n";
+            let _ = stream.write_all(head.as_bytes());
+            let _ = stream.flush();
+
+            thread::sleep(delay);
+        }
+        Reply::Status(status, body) => write_response(stream, status, &body),
+    }
+}
+
+fn write_response(stream: &mut TcpStream, status: u16, body: &str) {
+    let response = format!(
+        "HTTP/1.1 {status} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+
+    let _ = stream.write_all(response.as_bytes());
+}
@@ -0,0 +1,281 @@
+mod common;
+
+use common::{MockServer, Reply, TestDir, ccw, stderr};
+use std::{fs, process::Command};
+
+const CODE: &str = "use std::fmt;
+
+pub struct Counter {
+    value: u32,
+}
+
+impl Counter {
+    pub fn new() -> Self {
+        Self { value: 0 }
+    }
+
+    pub fn increment(&mut self) {
+        self.value += 1;
+    }
+
+    pub fn reset(&mut self) {
+        self.value = 0;
+    }
+}
+
+fn unrelated() -> u32 {
+    42
+}
+";
+
+const DIFF: &str = "diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -10,7 +10,7 @@ impl Counter {
+     }
+ 
+     pub fn increment(&mut self) {
+-        self.value = self.value + 1;
++        self.value += 1;
+     }
+ 
+     pub fn reset(&mut self) {
+diff --git a/README.md b/README.md
+--- a/README.md
++++ b/README.md
+@@ -1 +1 @@
+-Old
++New
+";
+
+#[test]
+fn checks_enclosing_function() {
+    let content = "{\"findings\":[{\"summary\":\"Overflow\",\"description\":\"Panics in debug\",\"recommendation\":\"Use checked_add\",\"severity\":\"medium\",\"start_line\":13,\"end_line\":13}]}";
+    let server = MockServer::start().reply(Reply::Chat(content.to_string()));
+    let dir = TestDir::new("diff-enclosing");
+    dir.write("src/lib.rs", CODE);
+
+    let output = ccw(
+        &server,
+        &dir.path,
+        &["--diff", "--diff-context=1", "--format=json"],
+        Some(DIFF),
+    );
+
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let requests = server.chat_requests();
+    assert_eq!(requests.len(), 1);
+
+    let text = requests[0].text();
+    assert!(
+        text.contains("changed region of src/lib.rs (lines 11-15)"),
+        "{text}"
+    );
+    assert!(text.contains("The lines changed by the diff are: 13."));
+    assert!(text.contains("13 |         self.value += 1;"));
+    assert!(!text.contains("pub fn reset"));
+    assert!(!text.contains("fn unrelated"));
+    assert!(text.contains("use std::fmt;"));
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let files = report["files"].as_array().unwrap();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0]["file_name"], "src/lib.rs");
+    assert_eq!(files[0]["findings"][0]["start_line"], 13);
+}
+
+#[test]
+fn merges_regions_and_keeps_top_level_changes_small() {
+    let diff = "--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,2 +1,2 @@
+-use std::io;
++use std::fmt;
+ 
+@@ -16,3 +16,3 @@
+     pub fn reset(&mut self) {
+-        self.value = 1;
++        self.value = 0;
+     }
+@@ -22,3 +22,3 @@
+ fn unrelated() -> u32 {
+-    41
++    42
+ }
+";
+    let server = MockServer::start();
+    let dir = TestDir::new("diff-merge");
+    dir.write("src/lib.rs", CODE);
+
+    let output = ccw(
+        &server,
+        &dir.path,
+        &["--diff", "--diff-context=0"],
+        Some(diff),
+    );
+
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let requests = server.chat_requests();
+    assert_eq!(requests.len(), 3);
+    assert!(requests[0].text().contains("(lines 1-1)"));
+    assert!(requests[1].text().contains("(lines 16-18)"));
+    assert!(requests[2].text().contains("(lines 21-23)"));
+}
+
+#[test]
+fn revision_is_not_an_option() {
+    let server = MockServer::start();
+    let dir = TestDir::new("diff-revision-option");
+    dir.write("main.rs", "fn main() {}\n");
+
+    init_repository(&dir);
+
+    let output = ccw(
+        &server,
+        &dir.path,
+        &["--mode=commit_review", "--commit=--output=leak.txt"],
+        None,
+    );
+
+    assert!(!output.status.success());
+    assert!(!dir.path.join("leak.txt").exists());
+    assert!(server.chat_requests().is_empty());
+}
+
+#[test]
+fn resolves_paths_from_subdirectory() {
+    let server = MockServer::start();
+    let dir = TestDir::new("diff-subdirectory");
+    dir.write("src/lib.rs", CODE);
+    init_repository(&dir);
+
+    let output = ccw(&server, &dir.path.join("src"), &["--diff"], Some(DIFF));
+
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let requests = server.chat_requests();
+    assert_eq!(requests.len(), 1);
+    assert!(requests[0].text().contains("changed region of lib.rs"));
+}
+
+#[test]
+fn fails_when_no_changed_file_is_found() {
+    let server = MockServer::start();
+    let dir = TestDir::new("diff-files-missing");
+
+    let output = ccw(&server, &dir.path, &["--diff"], Some(DIFF));
+
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("Skipping src/lib.rs: file not found"));
+    assert!(stderr(&output).contains("none of the 1 files changed by the diff were found"));
+    assert!(server.chat_requests().is_empty());
+}
+
+#[test]
+fn ignores_git_diff_configuration() {
+    let server = MockServer::start();
+    let dir = TestDir::new("diff-git-configuration");
+    dir.write("src/lib.rs", CODE);
+    init_repository(&dir);
+
+    git(&dir, &["config", "diff.external", "echo EXTERNAL"]);
+    git(&dir, &["config", "diff.noprefix", "true"]);
+    git(&dir, &["config", "color.ui", "always"]);
+
+    dir.write("src/lib.rs", &CODE.replace("value += 1", "value += 2"));
+    git(&dir, &["add", "src/lib.rs"]);
+
+    let output = ccw(&server, &dir.path, &["--diff", "--staged"], None);
+
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let output = ccw(
+        &server,
+        &dir.path,
+        &["--mode=commit_review", "--commit=HEAD"],
+        None,
+    );
+
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let requests = server.chat_requests();
+    assert_eq!(requests.len(), 2);
+    assert!(requests[0].text().contains("changed region of src/lib.rs"));
+
+    for request in requests {
+        let text = request.text();
+        assert!(!text.contains("EXTERNAL"), "{text}");
+        assert!(!text.contains('\u{1b}'), "{text}");
+    }
+
+    assert!(
+        server.chat_requests()[1]
+            .text()
+            .contains("+++ b/src/lib.rs")
+    );
+}
+
+#[test]
+fn output_dir_keeps_parent_paths_apart() {
+    let diff = "--- a/x/a.rs
++++ b/x/a.rs
+@@ -1 +1 @@
+-fn a() {}
++fn a() -> u32 { 1 }
+--- a/sub/x/a.rs
++++ b/sub/x/a.rs
+@@ -1 +1 @@
+-fn a() {}
++fn a() -> u32 { 2 }
+";
+    let server = MockServer::start();
+    let dir = TestDir::new("diff-output-dir");
+    dir.write("x/a.rs", "fn a() -> u32 { 1 }\n");
+    dir.write("sub/x/a.rs", "fn a() -> u32 { 2 }\n");
+    init_repository(&dir);
+
+    let output = ccw(
+        &server,
+        &dir.path.join("sub"),
+        &["--diff", "--output-dir=reports"],
+        Some(diff),
+    );
+
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let reports = dir.path.join("sub/reports");
+    let first = fs::read_to_string(reports.join("x/a.rs.md")).unwrap();
+    let second = fs::read_to_string(reports.join("x/a.rs-2.md")).unwrap();
+    assert!(first.contains("# ../x/a.rs"));
+    assert!(second.contains("# x/a.rs"));
+}
+
+fn git(dir: &TestDir, args: &[&str]) {
+    let status = Command::new("git")
+        .args(args)
+        .current_dir(&dir.path)
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+fn init_repository(dir: &TestDir) {
+    git(dir, &["init", "-q"]);
+    git(dir, &["add", "."]);
+    git(
+        dir,
+        &[
+            "-c",
+            "user.name=Test",
+            "-c",
+            "user.email=test@example.com",
+            "commit",
+            "-q",
+            "-m",
+            "Initial",
+        ],
+    );
+}
@@ -0,0 +1,141 @@
+mod common;
+
+use ccw::doc_generate;
+use common::{MockServer, Reply, TestDir, ccw, stderr, stdout};
+use std::fs;
+
+const CODE: &str = "use std::fmt;
+
+pub fn add(a: i32, b: i32) -> i32 {
+    a + b
+}
+
+fn helper() {}
+
+pub struct Point {
+    pub x: i32,
+}
+";
+
+const DOCUMENTED: &str = "```rust
+use std::fmt;
+
+/// Returns the sum of `a` and `b`.
+pub fn add(a: i32, b: i32) -> i32 {
+    a.wrapping_add(b)
+}
+
+fn helper() {}
+
+/// A point on a line.
+pub struct Point {
+    /// The position.
+    pub x: i32,
+}
+```";
+
+#[test]
+fn prints_doc_comments_as_patch() {
+    let server = MockServer::start().reply(Reply::Chat(DOCUMENTED.to_string()));
+    let dir = TestDir::new("doc-generate-rust");
+    dir.write("lib.rs", CODE);
+
+    let output = ccw(
+        &server,
+        &dir.path,
+        &["--mode=doc_generate", "--file=lib.rs"],
+        None,
+    );
+
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let stdout = stdout(&output);
+    assert!(!stdout.contains("```"));
+    assert!(!stdout.contains("wrapping_add"));
+    assert!(stdout.contains("Ignored 1 lines of lib.rs"));
+    assert!(stdout.contains(
+        "--- a/lib.rs
++++ b/lib.rs
+@@ -1,11 +1,14 @@
+ use std::fmt;
+ 
++/// Returns the sum of `a` and `b`.
+ pub fn add(a: i32, b: i32) -> i32 {
+     a + b
+ }
+ 
+ fn helper() {}
+ 
++/// A point on a line.
+ pub struct Point {
++    /// The position.
+     pub x: i32,
+ }
+"
+    ));
+
+    let messages = server.chat_requests()[0].messages();
+    assert!(messages[0].1.contains("CCW-DOC-GENERATE"));
+    assert!(messages[1].1.contains("Here is the Rust code from lib.rs:"));
+}
+
+#[test]
+fn writes_python_docstrings_to_output_file() {
+    let server = MockServer::start().reply(Reply::Chat(
+        "def area(width, height):\n    \"\"\"Return the area of a rectangle.\n\n    Both sides must be positive.\n    \"\"\"\n    return width * height\n"
+            .to_string(),
+    ));
+    let dir = TestDir::new("doc-generate-python");
+    dir.write(
+        "shapes.py",
+        "def area(width, height):\n    return width * height",
+    );
+
+    let output = ccw(
+        &server,
+        &dir.path,
+        &[
+            "--mode=doc_generate",
+            "--file=shapes.py",
+            "--output=docs.patch",
+        ],
+        None,
+    );
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        fs::read_to_string(dir.path.join("docs.patch")).unwrap(),
+        "--- a/shapes.py
++++ b/shapes.py
+@@ -1,2 +1,6 @@
+ def area(width, height):
++    \"\"\"Return the area of a rectangle.
++
++    Both sides must be positive.
++    \"\"\"
+     return width * height
+\\ No newline at end of file
+"
+    );
+}
+
+#[test]
+fn aligns_large_files_without_full_table() {
+    let original = (0..5000)
+        .map(|i| format!("let x{i} = {i};"))
+        .collect::<Vec<String>>();
+    let mut documented = original.clone();
+    documented.insert(4900, "/// Last.".to_string());
+    documented.insert(100, "/// First.".to_string());
+
+    let original = original.iter().map(String::as_str).collect::<Vec<&str>>();
+    let documented = documented.iter().map(String::as_str).collect::<Vec<&str>>();
+
+    assert_eq!(
+        doc_generate::insertions(&original, &documented),
+        vec![
+            (100, vec!["/// First.".to_string()]),
+            (4900, vec!["/// Last.".to_string()])
+        ]
+    );
+}
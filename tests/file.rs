@@ -0,0 +1,317 @@
+mod common;
+
+use common::{DEFAULT_CONTENT, MockServer, Reply, TestDir, ccw, stderr, stdout};
+use std::fs;
+
+const CODE: &str = "fn first() {}\nfn second() {}\nfn third() {}\nfn fourth() {}\n";
+
+#[test]
+fn line_slicing() {
+    let server = MockServer::start();
+    let dir = TestDir::new("line-slicing");
+    dir.write("main.rs", CODE);
+
+    let output = ccw(
+        &server,
+        &dir.path,
+        &["--file=main.rs", "--start-line=2", "--end-line=3"],
+        None,
+    );
+
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let text = server.chat_requests()[0].text();
+    assert!(!text.contains("first"));
+    assert!(text.contains("second"));
+    assert!(text.contains("third"));
+    assert!(!text.contains("fourth"));
+}
+
+#[test]
+fn line_slicing_from_zero() {
+    let content = "{\"findings\":[{\"summary\":\"Empty\",\"description\":\"Does nothing\",\"recommendation\":\"Remove it\",\"start_line\":2,\"end_line\":2}]}";
+    let server = MockServer::start().reply(Reply::Chat(content.to_string()));
+    let dir = TestDir::new("line-slicing-zero");
+    dir.write("main.rs", CODE);
+
+    let output = ccw(
+        &server,
+        &dir.path,
+        &[
+            "--file=main.rs",
+            "--start-line=0",
+            "--end-line=2",
+            "--format=json",
+        ],
+        None,
+    );
+
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["files"][0]["findings"][0]["start_line"], 2);
+}
+
+#[test]
+fn line_slicing_with_out_of_range_finding() {
+    let content = "{\"findings\":[{\"summary\":\"Empty\",\"description\":\"Does nothing\",\"recommendation\":\"Remove it\",\"start_line\":4294967295,\"end_line\":4294967295}]}";
+    let server = MockServer::start().reply(Reply::Chat(content.to_string()));
+    let dir = TestDir::new("line-slicing-out-of-range");
+    dir.write("main.rs", CODE);
+
+    let output = ccw(
+        &server,
+        &dir.path,
+        &[
+            "--file=main.rs",
+            "--start-line=2",
+            "--end-line=3",
+            "--format=json",
+        ],
+        None,
+    );
+
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let finding = &report["files"][0]["findings"][0];
+    assert_eq!(finding["summary"], "Empty");
+    assert!(finding["start_line"].is_null());
+    assert!(finding["location"].is_null());
+}
+
+#[test]
+fn line_numbers() {
+    let server = MockServer::start();
+    let dir = TestDir::new("line-numbers");
+    dir.write("main.rs", CODE);
+
+    let output = ccw(
+        &server,
+        &dir.path,
+        &[
+            "--file=main.rs",
+            "--start-line=2",
+            "--end-line=3",
+            "--line-numbers",
+        ],
+        None,
+    );
+
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let text = server.chat_requests()[0].text();
+    assert!(text.contains("2 | fn second() {}"));
+    assert!(text.contains("3 | fn third() {}"));
+}
+
+#[test]
+fn dir_scanning() {
+    let server = MockServer::start();
+    let dir = TestDir::new("dir-scanning");
+    dir.write("src/main.rs", CODE);
+    dir.write("src/lib.py", "print(1)\n");
+    dir.write("src/notes.txt", "not code\n");
+    dir.write("target/debug/build.rs", CODE);
+    dir.write(".gitignore", "target/\n");
+
+    let output = ccw(&server, &dir.path, &["--dir=.", "--exclude=**/*.py"], None);
+
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let requests = server.chat_requests();
+    assert_eq!(requests.len(), 1);
+    assert!(requests[0].text().contains("fn first() {}"));
+}
+
+#[test]
+fn unknown_language() {
+    let server = MockServer::start();
+    let dir = TestDir::new("unknown-language");
+    dir.write("notes.txt", "not code\n");
+
+    let output = ccw(&server, &dir.path, &["--file=notes.txt"], None);
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stderr(&output).contains("Skipping notes.txt"));
+    assert!(server.chat_requests().is_empty());
+}
+
+#[test]
+fn output_dir() {
+    let content = "{\"findings\":[{\"summary\":\"Unchecked input\",\"description\":\"Panics\",\"recommendation\":\"Validate\",\"severity\":\"high\"}]}";
+    let server = MockServer::start()
+        .reply(Reply::Chat(content.to_string()))
+        .reply(Reply::Chat("{\"findings\":[]}".to_string()));
+    let dir = TestDir::new("output-dir");
+    dir.write("src/lib.rs", CODE);
+    dir.write("src/main.rs", CODE);
+
+    let output = ccw(
+        &server,
+        &dir.path,
+        &[
+            "--dir=src",
+            "--format=json",
+            "--output-dir=reports",
+            "--no-cache",
+        ],
+        None,
+    );
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).is_empty());
+    assert!(stderr(&output).contains("File 1 of 2"));
+
+    let report = fs::read_to_string(dir.path.join("reports/src/lib.rs.json")).unwrap();
+    assert!(report.contains("Unchecked input"));
+    assert!(dir.path.join("reports/src/main.rs.json").exists());
+
+    let index: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(dir.path.join("reports/index.json")).unwrap())
+            .unwrap();
+    assert_eq!(index["files"][0]["verdict"], "1 finding, high");
+    assert_eq!(index["files"][0]["report"], "src/lib.rs.json");
+    assert_eq!(index["files"][1]["verdict"], "no findings");
+}
+
+#[test]
+fn output_dir_reserves_index() {
+    let server = MockServer::start();
+    let dir = TestDir::new("output-dir-index");
+    dir.write("index", "#!/bin/sh\necho index\n");
+
+    let output = ccw(
+        &server,
+        &dir.path,
+        &["--dir=.", "--format=json", "--output-dir=reports"],
+        None,
+    );
+
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let index: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(dir.path.join("reports/index.json")).unwrap())
+            .unwrap();
+    assert_eq!(index["files"][0]["file_name"], "./index");
+    assert_eq!(index["files"][0]["report"], "index-2.json");
+    assert!(dir.path.join("reports/index-2.json").exists());
+}
+
+#[test]
+fn output_file() {
+    let server = MockServer::start();
+    let dir = TestDir::new("output-file");
+    dir.write("main.rs", CODE);
+
+    let output = ccw(
+        &server,
+        &dir.path,
+        &["--mode=explain", "--file=main.rs", "--output=report.md"],
+        None,
+    );
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(!stdout(&output).contains(DEFAULT_CONTENT));
+
+    let report = fs::read_to_string(dir.path.join("report.md")).unwrap();
+    assert!(report.starts_with("# main.rs\n\nMock answer"));
+    assert!(report.contains("Text generated with ccw"));
+}
+
+#[test]
+fn html_report() {
+    let content = "{\"findings\":[{\"summary\":\"Empty <body>\",\"description\":\"Does nothing\",\"recommendation\":\"Remove it\",\"severity\":\"low\",\"start_line\":3,\"end_line\":3}]}";
+    let server = MockServer::start().reply(Reply::Chat(content.to_string()));
+    let dir = TestDir::new("html-report");
+    dir.write("src/main.rs", CODE);
+
+    let output = ccw(&server, &dir.path, &["--dir=src", "--format=html"], None);
+
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let html = stdout(&output);
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<summary>src</summary>"));
+    assert!(html.contains("<a href=\"#file-0\">main.rs</a>"));
+    assert!(html.contains("Empty &lt;body&gt;"));
+    assert!(html.contains("data-severity=\"low\""));
+    assert!(
+        html.contains(
+            "<span class=\"marked\"><span class=\"number\">3 | </span>fn third() {}</span>"
+        )
+    );
+    assert!(html.contains("<span class=\"number\">1 | </span>fn first() {}"));
+    assert!(html.contains("<option>checker</option>"));
+    assert!(html.contains("<td>Model</td><td>mock-coder:1b</td>"));
+    assert!(html.contains("<td>Duration</td>"));
+}
+
+#[test]
+fn unknown_format() {
+    let server = MockServer::start();
+    let dir = TestDir::new("unknown-format");
+    dir.write("main.rs", CODE);
+
+    let output = ccw(
+        &server,
+        &dir.path,
+        &["--file=main.rs", "--format=sarf"],
+        None,
+    );
+
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("unknown format sarf"));
+    assert!(server.chat_requests().is_empty());
+}
+
+#[test]
+fn shebang_detection() {
+    let server = MockServer::start();
+    let dir = TestDir::new("shebang-detection");
+    dir.write("bin/tool", "#!/usr/bin/env python3\nprint(1)\n");
+    dir.write("bin/blob", &"x".repeat(100_000));
+
+    let output = ccw(&server, &dir.path, &["--dir=bin"], None);
+
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let requests = server.chat_requests();
+    assert_eq!(requests.len(), 1);
+    assert!(requests[0].text().contains("Here is the Python code:"));
+}
+
+#[test]
+fn chunked_file_locations() {
+    let code = (1..=120)
+        .map(|i| format!("fn function_{i}() {{\n    let value = {i};\n}}\n\n"))
+        .collect::<String>();
+    let content = "{\"findings\":[{\"summary\":\"Unused\",\"description\":\"Value is unused\",\"recommendation\":\"Remove it\",\"start_line\":402,\"end_line\":402}]}";
+    let server = (0..20).fold(MockServer::start(), |server, _| {
+        server.reply(Reply::Chat(content.to_string()))
+    });
+    let dir = TestDir::new("chunked-file-locations");
+    dir.write("main.rs", &code);
+
+    let output = ccw(
+        &server,
+        &dir.path,
+        &["--file=main.rs", "--chunk-size=1024", "--format=json"],
+        None,
+    );
+
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let requests = server.chat_requests();
+    assert!(requests.len() > 1);
+    assert!(requests[1].text().contains("This is part 2 of"));
+    assert!(requests[1].text().contains(" | fn function_"));
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let findings = report["files"][0]["findings"].as_array().unwrap();
+    let lines = findings
+        .iter()
+        .filter_map(|finding| finding["start_line"].as_u64())
+        .collect::<Vec<u64>>();
+    assert_eq!(lines, vec![402]);
+}
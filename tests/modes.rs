@@ -0,0 +1,259 @@
+mod common;
+
+use common::{DEFAULT_CONTENT, MockServer, Reply, TestDir, ccw, stderr, stdout};
+
+const CODE: &str = "fn main() {\n    println!(\"Hello, world!\");\n}\n";
+const DIFF: &str = "diff --git a/src/main.rs b/src/main.rs\n+fn main() {}\n";
+
+fn run_file_mode(mode: &str, system_prompt: &str) {
+    let server = MockServer::start();
+    let dir = TestDir::new(&format!("mode-{mode}"));
+    dir.write("main.rs", CODE);
+
+    let output = ccw(
+        &server,
+        &dir.path,
+        &[&format!("--mode={mode}"), "--file=main.rs"],
+        None,
+    );
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains(DEFAULT_CONTENT));
+
+    let requests = server.chat_requests();
+    assert_eq!(requests.len(), 1);
+
+    let messages = requests[0].messages();
+    assert_eq!(messages[0].0, "system");
+    assert!(messages[0].1.contains(system_prompt));
+    assert!(requests[0].text().contains("Here is the Rust code:"));
+    assert!(requests[0].text().contains("Hello, world!"));
+}
+
+fn run_stdin_mode(mode: &str, system_prompt: &str) {
+    let server = MockServer::start();
+    let dir = TestDir::new(&format!("mode-{mode}"));
+
+    let output = ccw(&server, &dir.path, &[&format!("--mode={mode}")], Some(DIFF));
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains(DEFAULT_CONTENT));
+
+    let requests = server.chat_requests();
+    assert_eq!(requests.len(), 1);
+
+    let messages = requests[0].messages();
+    assert_eq!(messages[0].0, "system");
+    assert!(messages[0].1.contains(system_prompt));
+    assert!(requests[0].text().contains("+fn main() {}"));
+}
+
+#[test]
+fn ask() {
+    let server = MockServer::start();
+    let dir = TestDir::new("mode-ask");
+
+    let output = ccw(
+        &server,
+        &dir.path,
+        &["--mode=ask", "--question=What is a lifetime?"],
+        None,
+    );
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains(DEFAULT_CONTENT));
+
+    let requests = server.chat_requests();
+    assert_eq!(requests.len(), 1);
+    assert!(requests[0].messages()[0].1.contains("CCW-ASK"));
+    assert!(requests[0].text().contains("What is a lifetime?"));
+}
+
+#[test]
+fn checker() {
+    run_file_mode("checker", "CCW-CHECK");
+}
+
+#[test]
+fn commit_review() {
+    run_stdin_mode("commit_review", "CCW-COMMIT-REVIEW");
+}
+
+#[test]
+fn commit_summary() {
+    run_stdin_mode("commit_summary", "CCW-COMMIT-SUMMARY");
+}
+
+#[test]
+fn convert_to_rust() {
+    run_file_mode("convert_to_rust", "CCW-CONVERT-TO-RUST");
+}
+
+#[test]
+fn criteria_verify() {
+    let server = MockServer::start();
+    let dir = TestDir::new("mode-criteria-verify");
+    dir.write("criteria.md", "- Prints a greeting\n");
+
+    let output = ccw(
+        &server,
+        &dir.path,
+        &["--mode=criteria_verify", "--file=criteria.md"],
+        Some(DIFF),
+    );
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains(DEFAULT_CONTENT));
+
+    let requests = server.chat_requests();
+    assert_eq!(requests.len(), 1);
+    assert!(requests[0].messages()[0].1.contains("CCW-CRITERIA-VERIFY"));
+    assert!(requests[0].text().contains("Prints a greeting"));
+    assert!(requests[0].text().contains("+fn main() {}"));
+}
+
+#[test]
+fn design_advice() {
+    run_file_mode("design_advice", "CCW-DESIGN-ADVICE");
+}
+
+#[test]
+fn explain() {
+    run_file_mode("explain", "CCW-EXPLAIN");
+}
+
+#[test]
+fn performance() {
+    run_file_mode("performance", "CCW-PERFORMANCE");
+}
+
+#[test]
+fn task_generate() {
+    run_stdin_mode("task_generate", "CCW-TASK-GENERATE");
+}
+
+#[test]
+fn task_review() {
+    run_stdin_mode("task_review", "CCW-TASK-REVIEW");
+}
+
+#[test]
+fn test_generate() {
+    let server = MockServer::start();
+    let dir = TestDir::new("mode-test_generate");
+    dir.write("main.rs", CODE);
+
+    let output = ccw(
+        &server,
+        &dir.path,
+        &["--mode=test_generate", "--file=main.rs"],
+        None,
+    );
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains(DEFAULT_CONTENT));
+    assert!(stdout(&output).contains("Tests generated"));
+    assert!(!dir.path.join("tests.rs").exists());
+
+    let messages = server.chat_requests()[0].messages();
+    assert!(messages[0].1.contains("CCW-TEST-GENERATE"));
+    assert!(
+        messages[1]
+            .1
+            .contains("Here is the Rust code from main.rs:")
+    );
+}
+
+#[test]
+fn checker_json_findings() {
+    let content = "{\"findings\":[{\"summary\":\"Unchecked input\",\"description\":\"Panics\",\"recommendation\":\"Validate\",\"severity\":\"high\",\"start_line\":2,\"end_line\":2}]}";
+    let server = MockServer::start().reply(Reply::Chat(content.to_string()));
+    let dir = TestDir::new("checker-json");
+    dir.write("main.rs", CODE);
+
+    let output = ccw(
+        &server,
+        &dir.path,
+        &["--file=main.rs", "--format=json", "--fail-on=high"],
+        None,
+    );
+
+    assert_eq!(output.status.code(), Some(2), "{}", stderr(&output));
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let finding = &report["files"][0]["findings"][0];
+    assert_eq!(finding["summary"], "Unchecked input");
+    assert_eq!(finding["severity"], "high");
+    assert_eq!(finding["start_line"], 2);
+
+    let requests = server.chat_requests();
+    assert!(requests[0].body["format"].is_object());
+}
+
+#[test]
+fn checker_text_fail_on() {
+    let content = "==========
+Problem summary
+Path traversal in file download
+
+Severity: medium
+
+Problem detailed description
+Impact: lets an attacker read files the program should not allow.
+
+Recommendation to fix
+Canonicalize the path and check its prefix.
+==========";
+    let server = MockServer::start()
+        .reply(Reply::Chat(content.to_string()))
+        .reply(Reply::Chat(content.to_string()));
+    let dir = TestDir::new("checker-text-fail-on");
+    dir.write("main.rs", CODE);
+
+    let output = ccw(
+        &server,
+        &dir.path,
+        &["--file=main.rs", "--fail-on=medium", "--no-cache"],
+        None,
+    );
+
+    assert_eq!(output.status.code(), Some(2), "{}", stderr(&output));
+
+    let output = ccw(
+        &server,
+        &dir.path,
+        &["--file=main.rs", "--fail-on=high", "--no-cache"],
+        None,
+    );
+
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    assert!(
+        server.chat_requests()[0].messages()[0]
+            .1
+            .contains("Severity: critical | high | medium | low")
+    );
+}
+
+#[test]
+fn unknown_mode() {
+    let server = MockServer::start();
+    let dir = TestDir::new("unknown-mode");
+    dir.write("main.rs", CODE);
+
+    let output = ccw(
+        &server,
+        &dir.path,
+        &["--file=main.rs", "--mode=chekcer"],
+        None,
+    );
+
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("unknown mode chekcer"));
+    assert!(server.chat_requests().is_empty());
+
+    let output = ccw(&server, &dir.path, &["prompts", "show", "chekcer"], None);
+
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("unknown mode chekcer"));
+    assert!(stdout(&output).is_empty());
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use common::{MockServer, Reply, TestDir, ccw, stderr, stdout};
+use std::fs;
+
+#[test]
+fn writes_sibling_test_file() {
+    let server = MockServer::start().reply(Reply::Chat(
+        "```python\nfrom add import add\n\n\ndef test_add():\n    assert add(1, 2) == 3\n```"
+            .to_string(),
+    ));
+    let dir = TestDir::new("test-generate-python");
+    dir.write("add.py", "def add(a, b):\n    return a + b\n");
+
+    let output = ccw(
+        &server,
+        &dir.path,
+        &["--mode=test_generate", "--file=add.py", "--write-tests"],
+        None,
+    );
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("Tests written to test_add.py"));
+    assert_eq!(
+        fs::read_to_string(dir.path.join("test_add.py")).unwrap(),
+        "from add import add\n\n\ndef test_add():\n    assert add(1, 2) == 3\n"
+    );
+}
+
+#[test]
+fn writes_rust_tests_module() {
+    let server = MockServer::start().reply(Reply::Chat(
+        "```rust\n#[cfg(test)]\nmod tests {\n    use super::*;\n\n    #[test]\n    fn adds() {\n        assert_eq!(add(1, 2), 3);\n    }\n}\n```"
+            .to_string(),
+    ));
+    let dir = TestDir::new("test-generate-rust");
+    dir.write(
+        "add.rs",
+        "pub fn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n",
+    );
+
+    let output = ccw(
+        &server,
+        &dir.path,
+        &["--mode=test_generate", "--file=add.rs", "--write-tests"],
+        None,
+    );
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("mod tests;"));
+    assert_eq!(
+        fs::read_to_string(dir.path.join("add/tests.rs")).unwrap(),
+        "use super::*;\n\n#[test]\nfn adds() {\n    assert_eq!(add(1, 2), 3);\n}\n"
+    );
+}
+
+#[test]
+fn keeps_existing_test_file() {
+    let server = MockServer::start().reply(Reply::Chat("```go\npackage add\n```".to_string()));
+    let dir = TestDir::new("test-generate-existing");
+    dir.write("add.go", "package add\n");
+    dir.write("add_test.go", "// existing\n");
+
+    let output = ccw(
+        &server,
+        &dir.path,
+        &["--mode=test_generate", "--file=add.go", "--write-tests"],
+        None,
+    );
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stderr(&output).contains("file already exists"));
+    assert_eq!(
+        fs::read_to_string(dir.path.join("add_test.go")).unwrap(),
+        "// existing\n"
+    );
+}